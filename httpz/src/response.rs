@@ -1,4 +1,10 @@
-use http::Response;
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use futures::Stream;
+use http::{response::Builder, Response};
 
 use crate::Error;
 
@@ -22,3 +28,27 @@ where
         self?.into_response()
     }
 }
+
+/// is a stream of chunks which are sent to the client as the body of a response as they are produced.
+pub type BodyStream = Pin<Box<dyn Stream<Item = Vec<u8>> + Send + 'static>>;
+
+/// is put in the extensions of a response created with [streaming_response] so the server sends the stream as the body instead of the `Vec<u8>`.
+#[derive(Clone)]
+pub struct StreamingBody(Arc<Mutex<Option<BodyStream>>>);
+
+impl StreamingBody {
+    /// take the stream out of the body. This will return `None` if it has already been taken.
+    pub fn take(&self) -> Option<BodyStream> {
+        self.0.lock().ok()?.take()
+    }
+}
+
+/// build a response who's body is streamed to the client. Check the server supports this with [Server::supports_streaming](crate::Server::supports_streaming).
+pub fn streaming_response(
+    builder: Builder,
+    stream: impl Stream<Item = Vec<u8>> + Send + 'static,
+) -> Result<Response<Vec<u8>>, Error> {
+    Ok(builder
+        .extension(StreamingBody(Arc::new(Mutex::new(Some(Box::pin(stream))))))
+        .body(vec![])?)
+}
@@ -23,4 +23,13 @@ impl Server {
             _ => unreachable!(),
         }
     }
+
+    /// check if the server that handled this request supports sending the body of a response as a stream. Refer to [streaming_response](crate::streaming_response).
+    #[allow(unreachable_patterns)]
+    pub fn supports_streaming(&self) -> bool {
+        match self {
+            Self::Axum => true,
+            _ => unreachable!(),
+        }
+    }
 }
@@ -1,14 +1,16 @@
-use std::sync::Arc;
+use std::{convert::Infallible, sync::Arc};
 
 use axum::{
-    body::to_bytes,
+    body::{to_bytes, Body},
     extract::{Request, State},
+    response::IntoResponse,
     routing::{on, MethodFilter},
     Router,
 };
+use futures::StreamExt;
 use http::{header::CONTENT_LENGTH, HeaderMap, StatusCode};
 
-use crate::{Endpoint, HttpEndpoint, HttpResponse, Server};
+use crate::{Endpoint, HttpEndpoint, HttpResponse, Server, StreamingBody};
 
 pub use axum;
 
@@ -57,7 +59,8 @@ where
                                 StatusCode::BAD_REQUEST,
                                 HeaderMap::new(),
                                 err.to_string().as_bytes().to_vec(),
-                            );
+                            )
+                                .into_response();
                         }
                     };
 
@@ -69,14 +72,27 @@ where
                         .into_response()
                     {
                         Ok(resp) => {
-                            let (parts, body) = resp.into_parts();
-                            (parts.status, parts.headers, body)
+                            let (mut parts, body) = resp.into_parts();
+                            match parts
+                                .extensions
+                                .remove::<StreamingBody>()
+                                .and_then(|body| body.take())
+                            {
+                                Some(stream) => (
+                                    parts.status,
+                                    parts.headers,
+                                    Body::from_stream(stream.map(Ok::<_, Infallible>)),
+                                )
+                                    .into_response(),
+                                None => (parts.status, parts.headers, body).into_response(),
+                            }
                         }
                         Err(err) => (
                             StatusCode::INTERNAL_SERVER_ERROR,
                             HeaderMap::new(),
                             err.to_string().as_bytes().to_vec(),
-                        ),
+                        )
+                            .into_response(),
                     }
                 },
             ),
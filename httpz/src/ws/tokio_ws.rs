@@ -36,7 +36,11 @@ impl WebsocketUpgrade {
         THandler: FnOnce(Request, Box<dyn Websocket + Send>) -> TFut + Send + Sync + 'static,
         TFut: Future<Output = ()> + Send + 'static,
     {
-        WebSocketUpgradeResponse { req, handler }
+        WebSocketUpgradeResponse {
+            req,
+            handler,
+            protocol: None,
+        }
     }
 }
 
@@ -48,6 +52,19 @@ where
 {
     req: Request,
     handler: THandler,
+    protocol: Option<&'static str>,
+}
+
+impl<THandler, TFut> WebSocketUpgradeResponse<THandler, TFut>
+where
+    THandler: FnOnce(Request, Box<dyn Websocket + Send>) -> TFut + Send + Sync + 'static,
+    TFut: Future<Output = ()> + Send + 'static,
+{
+    /// sets the subprotocol the server selected from the `Sec-WebSocket-Protocol` header of the request.
+    pub fn protocol(mut self, protocol: &'static str) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
 }
 
 // By only spawning the tokio task here, we ensure we aren't spawning tasks if the user forgets to return the websocket upgrade response from the handler.
@@ -86,8 +103,7 @@ where
             None => return Ok(resp.status(StatusCode::BAD_REQUEST).body(vec![])?),
         };
 
-        // let sec_websocket_protocol = self.req.headers().get(header::SEC_WEBSOCKET_PROTOCOL).cloned();
-
+        let protocol = self.protocol;
         tokio::spawn(async move {
             let upgraded = on_upgrade.await.expect("connection upgrade failed");
 
@@ -101,7 +117,7 @@ where
             (self.handler)(self.req, Box::new(TokioSocket(socket))).await;
         });
 
-        let builder = resp
+        let mut builder = resp
             .status(StatusCode::SWITCHING_PROTOCOLS)
             .header(header::CONNECTION, UPGRADE)
             .header(header::UPGRADE, WEBSOCKET)
@@ -110,9 +126,9 @@ where
                 sign(sec_websocket_key.as_bytes()),
             );
 
-        // if let Some(protocol) = protocol {
-        //     builder = builder.header(header::SEC_WEBSOCKET_PROTOCOL, protocol);
-        // }
+        if let Some(protocol) = protocol {
+            builder = builder.header(header::SEC_WEBSOCKET_PROTOCOL, protocol);
+        }
 
         Ok(builder.body([].to_vec())?)
     }
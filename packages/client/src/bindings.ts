@@ -31,6 +31,7 @@ export type Request = Readonly<
       params: { path: string; input: NewOrOldInput }
     }
   | { method: 'subscriptionStop'; params: SubscriptionStop | null }
+  | { method: 'queryCancel'; params: { input: RequestId } }
 > & { jsonrpc?: string | null; id?: RequestId }
 
 /**
@@ -64,6 +65,7 @@ export type RequestInner = Readonly<
       params: { path: string; input: NewOrOldInput }
     }
   | { method: 'subscriptionStop'; params: SubscriptionStop | null }
+  | { method: 'queryCancel'; params: { input: RequestId } }
 >
 
 export type Response = Readonly<{
@@ -78,6 +80,10 @@ export type Response = Readonly<{
         data: {
           message: string
           code: number
+          data?: unknown
         }
       }
+    | {
+        type: 'complete'
+      }
 }>
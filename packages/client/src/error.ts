@@ -8,6 +8,7 @@ export class RSPCError implements Error {
   readonly name: string = 'RSPCError'
   readonly code: number
   readonly message: string
+  readonly data?: unknown
   readonly stack?: string
 
   /**
@@ -15,10 +16,12 @@ export class RSPCError implements Error {
    *
    * @param {number} code - The error code.
    * @param {string} message - The error message.
+   * @param {unknown} [data] - The typed error returned by the procedure, if it has one.
    */
-  constructor(code: number, message: string) {
+  constructor(code: number, message: string, data?: unknown) {
     this.code = code
     this.message = message
+    this.data = data
     this.stack = new Error().stack
   }
 
@@ -1,6 +1,7 @@
 export * from './error'
 export * from './typescript'
 export * from './v2/client'
+export * from './v2/codec'
 export * from './v2/observable'
 export * from './v2/links/link'
 export * from './v2/links/httpLink'
@@ -2,7 +2,7 @@
 export type OperationType = 'query' | 'mutation' | 'subscription' | 'subscriptionStop'
 
 // TODO
-export type ProcedureDef = { key: string; input: unknown; result: unknown }
+export type ProcedureDef = { key: string; input: unknown; result: unknown; error?: unknown }
 
 /**
  * This type represents the Typescript bindings which are generated from the router by Rust.
@@ -0,0 +1,35 @@
+/**
+ * A wire format for the messages sent to and received from the server. JSON is used by default.
+ * The codec must also be added to the router with `Config::codec` so the server understands it.
+ */
+export interface Codec {
+  /**
+   * The MIME type sent in the `Content-Type` and `Accept` headers. Eg. `application/msgpack`
+   */
+  contentType: string
+  /**
+   * The WebSocket subprotocol which selects this codec. Eg. `rspc.msgpack`
+   */
+  subprotocol: string
+  encode(value: unknown): string | Uint8Array
+  decode(data: string | ArrayBuffer | Uint8Array): unknown
+}
+
+/**
+ * The JSON codec used when no other codec is set.
+ */
+export const jsonCodec: Codec = {
+  contentType: 'application/json',
+  subprotocol: 'rspc.json',
+  encode: value => JSON.stringify(value),
+  decode: data => JSON.parse(typeof data === 'string' ? data : new TextDecoder().decode(data)),
+}
+
+/**
+ * The codec to decode a response with based on its `Content-Type` header.
+ * Falls back to JSON when the server answered with something else, Eg. an error from a proxy or a server without the codec.
+ */
+export function responseCodec(codec: Codec, contentType: string | null): Codec {
+  const mime = contentType?.split(';')[0]?.trim().toLowerCase()
+  return mime === codec.contentType.toLowerCase() ? codec : jsonCodec
+}
@@ -1,6 +1,8 @@
+import type { Codec } from '../codec'
 import type { Link, Operation } from './link'
 
 import { RSPCError } from '../../error'
+import { jsonCodec, responseCodec } from '../codec'
 
 type HTTPHeaders = Record<string, string | string[] | undefined>
 
@@ -14,6 +16,11 @@ type BaseHttpLinkOpts = {
    * Add ponyfill for AbortController
    */
   AbortController?: typeof AbortController | null
+  /**
+   * The wire format of the requests and responses. Defaults to JSON.
+   * The input of queries is always sent as JSON as it's part of the URL.
+   */
+  codec?: Codec
 }
 
 type HttpLinkOpts = BaseHttpLinkOpts & {
@@ -21,6 +28,11 @@ type HttpLinkOpts = BaseHttpLinkOpts & {
    * Headers to be set on outgoing requests or a callback that of said headers
    */
   headers?: HTTPHeaders | ((opts: { op: Operation }) => HTTPHeaders | Promise<HTTPHeaders>)
+  /**
+   * Add ponyfill for EventSource. Subscriptions are sent using Server-Sent Events.
+   * Note: `EventSource` doesn't support custom headers so `headers` are not sent with subscriptions.
+   */
+  EventSource?: typeof EventSource
 }
 
 /**
@@ -29,18 +41,48 @@ type HttpLinkOpts = BaseHttpLinkOpts & {
 export function httpLink(opts: HttpLinkOpts): Link {
   const fetchFn = opts.fetch || globalThis.fetch.bind(globalThis)
   const abortController = opts.AbortController || globalThis.AbortController.bind(globalThis)
+  const EventSourceImpl = opts.EventSource || globalThis.EventSource
+  const codec = opts.codec || jsonCodec
 
   return ({ op }) => {
     const abort = new abortController()
+    let eventSource: EventSource | undefined
     return {
       exec: async (resolve, reject) => {
-        if (op.type === 'subscription' || op.type === 'subscriptionStop') {
-          reject(
-            // TODO: Move to `RSPCError` type??
-            new Error(
-              `Subscribing to '${op.path}' failed as the HTTP transport does not support subscriptions! Maybe try using the websocket transport?`
+        // Stopping is handled by `abort` closing the `EventSource`.
+        if (op.type === 'subscriptionStop') return
+
+        if (op.type === 'subscription') {
+          if (!EventSourceImpl) {
+            reject(
+              // TODO: Move to `RSPCError` type??
+              new Error(
+                `Subscribing to '${op.path}' failed as 'EventSource' is not available! Maybe try using the websocket transport?`
+              )
             )
+            return
+          }
+
+          const params = new URLSearchParams()
+          if (op.input !== undefined) {
+            params.append('input', JSON.stringify(op.input))
+          }
+          const paramsStr = params.toString()
+          eventSource = new EventSourceImpl(
+            `${opts.url}/${op.path}${paramsStr.length > 0 ? `?${paramsStr}` : ''}`
           )
+          eventSource.addEventListener('message', event => {
+            const { result } = JSON.parse(event.data)
+            if (result.type === 'event') {
+              resolve(result.data)
+            } else if (result.type === 'error') {
+              const { code, message, data } = result.data
+              reject(new RSPCError(code, message, data))
+              eventSource?.close()
+            } else if (result.type === 'complete') {
+              eventSource?.close()
+            }
+          })
           return
         }
 
@@ -69,8 +111,11 @@ export function httpLink(opts: HttpLinkOpts): Link {
           }
         } else if (op.type === 'mutation') {
           method = 'POST'
-          body = JSON.stringify(op.input || {})
-          headers.set('Content-Type', 'application/json')
+          body = codec.encode(op.input || {})
+          headers.set('Content-Type', codec.contentType)
+        }
+        if (opts.codec) {
+          headers.set('Accept', codec.contentType)
         }
 
         const paramsStr = params.toString()
@@ -84,11 +129,13 @@ export function httpLink(opts: HttpLinkOpts): Link {
           }
         )
         // TODO: validate response
-        const respBody = await resp.json()
+        const respBody: any = responseCodec(codec, resp.headers.get('Content-Type')).decode(
+          await resp.arrayBuffer()
+        )
         const { type, data } = respBody.result
         if (type === 'error') {
-          const { code, message } = data
-          reject(new RSPCError(code, message))
+          const { code, message, data: errorData } = data
+          reject(new RSPCError(code, message, errorData))
         } else {
           resolve(data)
         }
@@ -96,6 +143,7 @@ export function httpLink(opts: HttpLinkOpts): Link {
       execBatch: async () => {},
       abort() {
         abort.abort()
+        eventSource?.close()
       },
     }
   }
@@ -106,6 +154,10 @@ type HttpBatchLinkOpts = BaseHttpLinkOpts & {
    * Headers to be set on outgoing requests or a callback that of said headers
    */
   headers?: HTTPHeaders | ((opts: { ops: Operation[] }) => HTTPHeaders | Promise<HTTPHeaders>)
+  /**
+   * Stream the responses of a batch as newline-delimited JSON so each operation resolves as soon as it finishes on the server instead of waiting for the whole batch.
+   */
+  stream?: boolean
 }
 
 type BatchedItem = {
@@ -122,6 +174,7 @@ type BatchedItem = {
 export function httpBatchLink(opts: HttpBatchLinkOpts): Link {
   const fetchFn = opts.fetch || globalThis.fetch.bind(globalThis)
   const abortController = opts.AbortController || globalThis.AbortController.bind(globalThis)
+  const codec = opts.codec || jsonCodec
 
   const pushBatch = async (batch: BatchedItem[]) => {
     let headers = new Headers()
@@ -141,10 +194,19 @@ export function httpBatchLink(opts: HttpBatchLinkOpts): Link {
       }
     }
 
+    if (opts.stream) {
+      headers.set('Accept', 'application/x-ndjson')
+    } else if (opts.codec) {
+      headers.set('Accept', codec.contentType)
+    }
+    if (opts.codec) {
+      headers.set('Content-Type', codec.contentType)
+    }
+
     const resp = await fetchFn(`${opts.url}/_batch`, {
       method: 'POST',
       headers,
-      body: JSON.stringify(
+      body: codec.encode(
         batch.map(({ op }) => ({
           id: op.id,
           method: op.type,
@@ -156,9 +218,45 @@ export function httpBatchLink(opts: HttpBatchLinkOpts): Link {
       ),
     })
 
+    // The server falls back to a JSON array if it can't stream the response.
+    if (resp.headers.get('Content-Type')?.startsWith('application/x-ndjson') && resp.body) {
+      // Responses arrive in the order they finished so they are matched to the operations by their id.
+      const pending = new Map(batch.map(item => [item.op.id, item]))
+      const reader = resp.body.getReader()
+      const decoder = new TextDecoder()
+      let buffer = ''
+      for (;;) {
+        const { done, value } = await reader.read()
+        buffer += decoder.decode(value, { stream: !done })
+        const lines = buffer.split('\n')
+        buffer = lines.pop() ?? ''
+        for (const line of lines) {
+          if (line.trim() === '') continue
+          const item = JSON.parse(line)
+          const batchItem = pending.get(item.id)
+          pending.delete(item.id)
+          if (batchItem) resolveBatchItem(batchItem, item)
+        }
+        if (done) break
+      }
+
+      if (pending.size !== 0) {
+        console.error('rspc: batch response length mismatch!')
+      }
+      return
+    }
+
     // TODO: Validate response
-    const body: unknown = await resp.json()
+    const body: any = responseCodec(codec, resp.headers.get('Content-Type')).decode(
+      await resp.arrayBuffer()
+    )
     if (!Array.isArray(body)) {
+      // The whole batch failed. Eg. the request couldn't be parsed or the context function errored.
+      if (body?.result?.type === 'error') {
+        for (const batchItem of batch) resolveBatchItem(batchItem, body)
+        return
+      }
+
       console.error('rspc: batch response not an array!')
       return
     }
@@ -170,17 +268,24 @@ export function httpBatchLink(opts: HttpBatchLinkOpts): Link {
 
     for (const [i, item] of body.entries()) {
       const batchItem = batch[i]
-      if (!batchItem || batchItem.abort.signal?.aborted) {
-        continue
-      }
+      if (batchItem) resolveBatchItem(batchItem, item)
+    }
+  }
 
-      if (item.result.type === 'response') {
-        batch[i]?.resolve(item.result.data)
-      } else if (item.result.type === 'error') {
-        batch[i]?.reject(new RSPCError(item.result.data.code, item.result.data.message))
-      } else {
-        console.error('rspc: batch response type mismatch!')
-      }
+  // TODO: Validate response
+  const resolveBatchItem = (batchItem: BatchedItem, item: any) => {
+    if (batchItem.abort.signal?.aborted) {
+      return
+    }
+
+    if (item.result.type === 'response') {
+      batchItem.resolve(item.result.data)
+    } else if (item.result.type === 'error') {
+      batchItem.reject(
+        new RSPCError(item.result.data.code, item.result.data.message, item.result.data.data)
+      )
+    } else {
+      console.error('rspc: batch response type mismatch!')
     }
   }
 
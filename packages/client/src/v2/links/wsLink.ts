@@ -1,7 +1,9 @@
 import type { Request as RspcRequest } from '../../bindings'
+import type { Codec } from '../codec'
 import type { Link } from './link'
 
 import { RSPCError } from '../../error'
+import { jsonCodec } from '../codec'
 
 const timeouts = [1000, 2000, 5000, 10000] // In milliseconds
 
@@ -11,10 +13,17 @@ type WsLinkOpts = {
    * Add ponyfill for WebSocket
    */
   WebSocket?: typeof WebSocket
+  /**
+   * The wire format of the messages. Defaults to JSON.
+   * It's negotiated with the server as a WebSocket subprotocol.
+   */
+  codec?: Codec
 }
 
 function newWsManager(opts: WsLinkOpts) {
   const WebSocket = opts.WebSocket || globalThis.WebSocket.bind(globalThis)
+  const codec = opts.codec || jsonCodec
+  const protocols = opts.codec ? [opts.codec.subprotocol] : undefined
   const activeMap = new Map<
     string,
     {
@@ -26,7 +35,7 @@ function newWsManager(opts: WsLinkOpts) {
   let ws: WebSocket
   const attachEventListeners = () => {
     ws.addEventListener('message', event => {
-      const { id, result } = JSON.parse(event.data)
+      const { id, result } = codec.decode(event.data) as any
       if (activeMap.has(id)) {
         if (result.type === 'event') {
           activeMap.get(id)?.resolve(result.data)
@@ -34,8 +43,10 @@ function newWsManager(opts: WsLinkOpts) {
           activeMap.get(id)?.resolve(result.data)
           activeMap.delete(id)
         } else if (result.type === 'error') {
-          const { message, code } = result.data
-          activeMap.get(id)?.reject(new RSPCError(code, message))
+          const { message, code, data } = result.data
+          activeMap.get(id)?.reject(new RSPCError(code, message, data))
+          activeMap.delete(id)
+        } else if (result.type === 'complete') {
           activeMap.delete(id)
         } else {
           console.error(`rspc: received event of unknown type '${result.type}'`)
@@ -56,7 +67,8 @@ function newWsManager(opts: WsLinkOpts) {
       (Math.floor(Math.random() * 5000 /* 5 Seconds */) + 1)
 
     setTimeout(() => {
-      let newWs = new WebSocket(opts.url)
+      let newWs = new WebSocket(opts.url, protocols)
+      newWs.binaryType = 'arraybuffer'
       new Promise(function (resolve, reject) {
         newWs.addEventListener('open', () => resolve(null))
         newWs.addEventListener('close', reject)
@@ -70,7 +82,8 @@ function newWsManager(opts: WsLinkOpts) {
   }
 
   const initWebsocket = () => {
-    ws = new WebSocket(opts.url)
+    ws = new WebSocket(opts.url, protocols)
+    ws.binaryType = 'arraybuffer'
     attachEventListeners()
   }
   initWebsocket()
@@ -89,7 +102,7 @@ function newWsManager(opts: WsLinkOpts) {
   return [
     activeMap,
     (data: RspcRequest | RspcRequest[]) =>
-      awaitWebsocketReady().then(() => ws.send(JSON.stringify(data))),
+      awaitWebsocketReady().then(() => ws.send(codec.encode(data))),
   ] as const
 }
 
@@ -133,12 +146,17 @@ export function wsLink(opts: WsLinkOpts): Link {
         finished = true
 
         // TODO: We should probs still use dataloader internally to deal with create/delete events due to React strict mode.
+        if (op.type === 'subscription') {
+          send({
+            id: op.id,
+            method: 'subscriptionStop',
+            params: null,
+          })
+        } else if (activeMap.has(op.id)) {
+          // The query or mutation hasn't responded yet so tell the server to stop running it.
+          send({ method: 'queryCancel', params: { input: op.id } })
+        }
         activeMap.delete(op.id)
-        send({
-          id: op.id,
-          method: 'subscriptionStop',
-          params: null,
-        })
       },
     }
   }
@@ -214,6 +232,10 @@ export function wsBatchLink(opts: WsLinkOpts): Link {
               params: null,
             })
             queueBatch()
+          } else if (activeMap.has(op.id)) {
+            // The query or mutation was already sent but hasn't responded yet so tell the server to stop running it.
+            batch.push({ method: 'queryCancel', params: { input: op.id } })
+            queueBatch()
           }
         } else {
           batch.splice(subscribeEventIdx, 1)
@@ -24,8 +24,10 @@ export function tauriLink(): Link {
         activeMap.get(id)?.resolve(result.data);
         activeMap.delete(id);
       } else if (result.type === "error") {
-        const { message, code } = result.data;
-        activeMap.get(id)?.reject(new RSPCError(code, message));
+        const { message, code, data } = result.data;
+        activeMap.get(id)?.reject(new RSPCError(code, message, data));
+        activeMap.delete(id);
+      } else if (result.type === "complete") {
         activeMap.delete(id);
       } else {
         console.error(`rspc: received event of unknown type '${result.type}'`);
@@ -101,6 +103,10 @@ export function tauriLink(): Link {
               params: null,
             });
             queueBatch();
+          } else if (activeMap.has(op.id)) {
+            // The query or mutation was already sent but hasn't responded yet so tell the server to stop running it.
+            batch.push({ method: "queryCancel", params: { input: op.id } });
+            queueBatch();
           }
         } else {
           batch.splice(subscribeEventIdx, 1);
@@ -5,6 +5,7 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 
 use futures::Stream;
@@ -12,7 +13,11 @@ use pin_project::pin_project;
 use serde::de::DeserializeOwned;
 use specta::Type;
 
-use crate::{alpha::Executable2, internal::RequestContext, ExecError};
+use crate::{
+    alpha::Executable2,
+    internal::{ProcedureDocs, ProcedureOptions, RequestContext},
+    ExecError,
+};
 
 use super::{
     AlphaLayer, AlphaMiddlewareBuilderLikeCompat, AlphaRequestLayer, FutureMarker, IntoProcedure,
@@ -33,6 +38,9 @@ pub struct AlphaProcedure<R, RMarker, TMiddleware>(
     // Is `None` after `.build()` is called. `.build()` can't take `self` cause dyn safety.
     Option<TMiddleware>,
     RMarker,
+    // The options set with `.timeout()`, `.meta()`, `.description()` and `.deprecated()`.
+    // The timeout falls back to the router's timeout when `None`.
+    ProcedureOptions,
 )
 where
     TMiddleware: AlphaMiddlewareBuilderLike;
@@ -41,8 +49,20 @@ impl<TMiddleware, R, RMarker> AlphaProcedure<R, RMarker, TMiddleware>
 where
     TMiddleware: AlphaMiddlewareBuilderLike,
 {
+    #[track_caller]
     pub fn new_from_resolver(k: RMarker, mw: TMiddleware, resolver: R) -> Self {
-        Self(Some(resolver), Some(mw), k)
+        Self(
+            Some(resolver),
+            Some(mw),
+            k,
+            ProcedureOptions {
+                timeout: None,
+                meta: None,
+                location: Location::caller(),
+                docs: ProcedureDocs::default(),
+                prefix: String::new(),
+            },
+        )
     }
 }
 
@@ -57,7 +77,18 @@ where
     where
         TMiddleware: AlphaMiddlewareBuilderLike<Ctx = TCtx>,
     {
-        AlphaProcedure(Some(MissingResolver::default()), Some(mw), ())
+        AlphaProcedure(
+            Some(MissingResolver::default()),
+            Some(mw),
+            (),
+            ProcedureOptions {
+                timeout: None,
+                meta: None,
+                location: Location::caller(),
+                docs: ProcedureDocs::default(),
+                prefix: String::new(),
+            },
+        )
     }
 }
 
@@ -65,6 +96,7 @@ impl<TMiddleware> AlphaProcedure<MissingResolver<TMiddleware::LayerCtx>, (), TMi
 where
     TMiddleware: AlphaMiddlewareBuilderLike,
 {
+    #[track_caller]
     pub fn query<R, RMarker>(
         mut self,
         builder: R,
@@ -81,6 +113,7 @@ where
         )
     }
 
+    #[track_caller]
     pub fn mutation<R, RMarker>(
         mut self,
         builder: R,
@@ -97,6 +130,7 @@ where
         )
     }
 
+    #[track_caller]
     pub fn subscription<R, RMarker>(
         mut self,
         builder: R,
@@ -142,6 +176,37 @@ where
     }
 }
 
+impl<R, RMarker, TMiddleware> AlphaProcedure<R, RMarker, TMiddleware>
+where
+    R: ResolverFunction<RMarker>,
+    TMiddleware: AlphaMiddlewareBuilderLike,
+{
+    /// sets the maximum amount of time the procedure can take before it's cancelled and a `Timeout` error is returned.
+    /// For subscriptions this only applies to setting up the stream.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.3.timeout = Some(timeout);
+        self
+    }
+
+    /// attaches metadata to the procedure. It can be read by middleware using [RequestContext::meta].
+    pub fn meta<TMeta: Send + Sync + 'static>(mut self, meta: TMeta) -> Self {
+        self.3.meta = Some(Arc::new(meta));
+        self
+    }
+
+    /// describes the procedure. The description is exported as JSDoc in the Typescript bindings.
+    pub fn description(mut self, description: impl Into<Cow<'static, str>>) -> Self {
+        self.3.docs.description = Some(description.into());
+        self
+    }
+
+    /// marks the procedure as deprecated. It's exported with a JSDoc `@deprecated` tag and calling it logs a warning.
+    pub fn deprecated(mut self, reason: impl Into<Cow<'static, str>>) -> Self {
+        self.3.docs.deprecated = Some(reason.into());
+        self
+    }
+}
+
 impl<R, RMarker, TMiddleware> IntoProcedure<TMiddleware::Ctx>
     for AlphaProcedure<R, RequestLayerMarker<RMarker>, TMiddleware>
 where
@@ -187,6 +252,11 @@ where
                     Location::caller()
                 )
             }),
+            ProcedureOptions {
+                timeout: self.3.timeout.or(ctx.default_timeout),
+                prefix: ctx.prefix.clone(),
+                ..self.3.clone()
+            },
         );
     }
 }
@@ -231,6 +301,11 @@ where
                     Location::caller()
                 )
             }),
+            ProcedureOptions {
+                timeout: self.3.timeout.or(ctx.default_timeout),
+                prefix: ctx.prefix.clone(),
+                ..self.3.clone()
+            },
         );
     }
 }
@@ -244,6 +319,7 @@ where
     type Middleware = TMiddleware;
     type LayerCtx = TMiddleware::LayerCtx;
 
+    #[track_caller]
     fn query<R, RMarker>(
         mut self,
         builder: R,
@@ -260,6 +336,7 @@ where
         )
     }
 
+    #[track_caller]
     fn mutation<R, RMarker>(
         mut self,
         builder: R,
@@ -276,6 +353,7 @@ where
         )
     }
 
+    #[track_caller]
     fn subscription<R, RMarker>(
         mut self,
         builder: R,
@@ -1,9 +1,9 @@
-use std::{borrow::Cow, marker::PhantomData};
+use std::{borrow::Cow, marker::PhantomData, time::Duration};
 
 use specta::TypeMap;
 
 use crate::{
-    internal::{BaseMiddleware, ProcedureStore},
+    internal::{join_prefixes, BaseMiddleware, ProcedureStore},
     is_invalid_router_prefix, Config, Router, RouterBuilder, RouterBuilderLike,
 };
 
@@ -18,6 +18,7 @@ where
     TCtx: Send + Sync + 'static,
 {
     procedures: ProcedureList<TCtx>,
+    timeout: Option<Duration>,
     dangerously_map_types: Option<Box<dyn FnOnce(&mut TypeMap) + Send + Sync + 'static>>,
 }
 
@@ -39,6 +40,7 @@ where
     pub fn new() -> Self {
         Self {
             procedures: Vec::new(),
+            timeout: None,
             dangerously_map_types: None,
         }
     }
@@ -49,6 +51,12 @@ where
         self
     }
 
+    /// sets the timeout for every procedure in this router which doesn't set it's own timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     // TODO
     // pub fn merge(self, prefix: &'static str, r: impl RouterBuilderLike<TCtx>) -> Self {
     //     // TODO: disallow `.` in prefix
@@ -56,6 +64,7 @@ where
     //     todo!();
     // }
 
+    #[track_caller]
     pub fn query<R, RMarker>(
         self,
         builder: R,
@@ -72,6 +81,7 @@ where
         )
     }
 
+    #[track_caller]
     pub fn mutation<R, RMarker>(
         self,
         builder: R,
@@ -88,6 +98,7 @@ where
         )
     }
 
+    #[track_caller]
     pub fn subscription<R, RMarker>(
         self,
         builder: R,
@@ -117,12 +128,16 @@ where
             );
         }
 
-        self.procedures.extend(
-            router
-                .procedures()
-                .into_iter()
-                .map(|(key, procedure)| (Cow::Owned(format!("{}{}", prefix, key)), procedure)),
-        );
+        self.procedures
+            .extend(router.procedures().into_iter().map(|(key, procedure)| {
+                (
+                    Cow::Owned(format!("{}{}", prefix, key)),
+                    Box::new(WithPrefix {
+                        prefix: prefix.clone(),
+                        procedure,
+                    }) as Box<dyn IntoProcedure<TCtx>>,
+                )
+            }));
 
         self
     }
@@ -148,6 +163,8 @@ where
             queries: &mut queries,
             mutations: &mut mutations,
             subscriptions: &mut subscriptions,
+            default_timeout: self.timeout,
+            prefix: String::new(),
         };
 
         for (key, mut procedure) in self.procedures.into_iter() {
@@ -177,6 +194,8 @@ where
             queries: &mut queries,
             mutations: &mut mutations,
             subscriptions: &mut subscriptions,
+            default_timeout: self.timeout,
+            prefix: String::new(),
         };
 
         for (key, mut procedure) in self.procedures.into_iter() {
@@ -210,7 +229,49 @@ where
 
 impl<TCtx: Send + Sync + 'static> AlphaRouterBuilderLike<TCtx> for AlphaRouter<TCtx> {
     fn procedures(self) -> ProcedureList<TCtx> {
-        self.procedures
+        match self.timeout {
+            Some(timeout) => self
+                .procedures
+                .into_iter()
+                .map(|(key, procedure)| {
+                    (
+                        key,
+                        Box::new(WithTimeout { timeout, procedure })
+                            as Box<dyn IntoProcedure<TCtx>>,
+                    )
+                })
+                .collect(),
+            None => self.procedures,
+        }
+    }
+}
+
+// Applies the timeout of a merged router to it's procedures without overriding the timeout of a more specific router or procedure.
+struct WithTimeout<TCtx> {
+    timeout: Duration,
+    procedure: Box<dyn IntoProcedure<TCtx>>,
+}
+
+impl<TCtx: 'static> IntoProcedure<TCtx> for WithTimeout<TCtx> {
+    fn build(&mut self, key: Cow<'static, str>, ctx: &mut IntoProcedureCtx<'_, TCtx>) {
+        let parent_timeout = ctx.default_timeout.replace(self.timeout);
+        self.procedure.build(key, ctx);
+        ctx.default_timeout = parent_timeout;
+    }
+}
+
+// Records the prefix a procedure was merged with so it doesn't have to be guessed from the key, which can contain `.`'s.
+struct WithPrefix<TCtx> {
+    prefix: String,
+    procedure: Box<dyn IntoProcedure<TCtx>>,
+}
+
+impl<TCtx: 'static> IntoProcedure<TCtx> for WithPrefix<TCtx> {
+    fn build(&mut self, key: Cow<'static, str>, ctx: &mut IntoProcedureCtx<'_, TCtx>) {
+        let parent_prefix = ctx.prefix.clone();
+        ctx.prefix = join_prefixes(&parent_prefix, &self.prefix);
+        self.procedure.build(key, ctx);
+        ctx.prefix = parent_prefix;
     }
 }
 
@@ -219,6 +280,8 @@ pub struct IntoProcedureCtx<'a, TCtx> {
     pub queries: &'a mut ProcedureStore<TCtx>,
     pub mutations: &'a mut ProcedureStore<TCtx>,
     pub subscriptions: &'a mut ProcedureStore<TCtx>,
+    pub default_timeout: Option<Duration>,
+    pub prefix: String,
 }
 
 pub trait IntoProcedure<TCtx>: 'static {
@@ -241,6 +304,7 @@ where
             mutations: r.mutations,
             subscriptions: r.subscriptions,
             typ_store: r.typ_store,
+            timeout: None,
             phantom: PhantomData,
         }
     }
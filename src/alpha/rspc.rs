@@ -72,6 +72,7 @@ where
         })
     }
 
+    #[track_caller]
     pub fn query<R, RMarker>(
         self,
         resolver: R,
@@ -88,6 +89,7 @@ where
         )
     }
 
+    #[track_caller]
     pub fn mutation<R, RMarker>(
         self,
         resolver: R,
@@ -104,6 +106,7 @@ where
         )
     }
 
+    #[track_caller]
     pub fn subscription<R, RMarker>(
         self,
         resolver: R,
@@ -6,7 +6,7 @@ use specta::{Type, TypeMap};
 
 use specta_typescript as ts;
 
-use crate::{alpha_stable::AlphaRequestLayer, internal::ProcedureDataType};
+use crate::{alpha_stable::AlphaRequestLayer, internal::ProcedureDataType, ResolverError};
 
 use super::{FutureMarker, RequestLayerMarker, StreamLayerMarker, StreamMarker};
 
@@ -22,6 +22,7 @@ pub trait ResolverFunction<TMarker>: Send + Sync + 'static {
     type ResultMarker;
 
     type RawResult: Type; // TODO: Can we remove this. It's basically `Self::Result`
+    type RawError: ResolverError;
 
     fn exec(&self, ctx: Self::LayerCtx, arg: Self::Arg) -> Self::Result;
 
@@ -33,6 +34,7 @@ pub trait ResolverFunction<TMarker>: Send + Sync + 'static {
             key,
             input: <TMiddleware::Arg<Self::Arg> as Type>::reference(type_map, &[]).inner,
             result: <Self::RawResult as Type>::reference(type_map, &[]).inner,
+            error: <Self::RawError as ResolverError>::typedef(type_map),
         })
     }
 }
@@ -59,6 +61,7 @@ where
     type ResultMarker = RequestLayerMarker<TResultMarker>;
     type RequestMarker = TResultMarker;
     type RawResult = TResult::Result;
+    type RawError = TResult::Error;
 
     fn exec(&self, ctx: Self::LayerCtx, arg: Self::Arg) -> Self::Result {
         self(ctx, arg)
@@ -83,6 +86,7 @@ where
     type ResultMarker = StreamLayerMarker<TResultMarker>;
     type RequestMarker = TResultMarker;
     type RawResult = TResult::Result;
+    type RawError = TResult::Error;
 
     fn exec(&self, ctx: Self::LayerCtx, arg: Self::Arg) -> Self::Result {
         self(ctx, arg)
@@ -7,7 +7,7 @@ use std::{
 
 use std::future::ready;
 
-use futures::{stream::Once, Stream};
+use futures::{future::Map, stream::Once, FutureExt, Stream};
 
 use futures::stream::once;
 
@@ -16,7 +16,7 @@ use serde::Serialize;
 use serde_json::Value;
 use specta::Type;
 
-use crate::{Error, ExecError};
+use crate::{Error, ExecError, ResolverError, Typed};
 
 use super::{PinnedOption, PinnedOptionProj};
 
@@ -31,6 +31,7 @@ pub enum FutureMarker {}
 
 pub trait AlphaRequestLayer<TMarker> {
     type Result: Type;
+    type Error: ResolverError;
     type Stream: Stream<Item = Result<Value, ExecError>> + Send + 'static;
     type Type;
 
@@ -47,6 +48,7 @@ where
     T: Serialize + Type,
 {
     type Result = T;
+    type Error = Error;
     type Stream = Once<Ready<Result<Value, ExecError>>>;
     type Type = FutureMarker;
 
@@ -57,6 +59,26 @@ where
     }
 }
 
+#[doc(hidden)]
+pub enum AlphaTypedResultMarker {}
+
+impl<T, TErr> AlphaRequestLayer<AlphaTypedResultMarker> for Typed<Result<T, TErr>>
+where
+    T: Serialize + Type,
+    TErr: ResolverError,
+{
+    type Result = T;
+    type Error = TErr;
+    type Stream = Once<Ready<Result<Value, ExecError>>>;
+    type Type = FutureMarker;
+
+    fn exec(self) -> Self::Stream {
+        AlphaRequestLayer::<AlphaResultMarker>::exec(
+            self.0.map_err(ResolverError::into_resolver_error),
+        )
+    }
+}
+
 #[pin_project(project = FutureSerializeFutureProj)]
 pub struct FutureSerializeFuture<TFut, T>(#[pin] TFut, PhantomData<T>);
 
@@ -87,6 +109,7 @@ where
     T: Serialize + Type + Send + 'static,
 {
     type Result = T;
+    type Error = Error;
     type Stream = Once<FutureSerializeResultFuture<TFut, T>>;
     type Type = FutureMarker;
 
@@ -95,6 +118,30 @@ where
     }
 }
 
+#[doc(hidden)]
+pub enum AlphaFutureTypedResultMarker {}
+
+impl<TFut, T, TErr> AlphaRequestLayer<AlphaFutureTypedResultMarker> for TFut
+where
+    TFut: Future<Output = Typed<Result<T, TErr>>> + Send + 'static,
+    T: Serialize + Type + Send + 'static,
+    TErr: ResolverError,
+{
+    type Result = T;
+    type Error = TErr;
+    type Stream = Once<Map<TFut, fn(TFut::Output) -> Result<Value, ExecError>>>;
+    type Type = FutureMarker;
+
+    fn exec(self) -> Self::Stream {
+        let map: fn(TFut::Output) -> Result<Value, ExecError> = |v| {
+            v.0.map_err(|err| ExecError::ErrResolverError(err.into_resolver_error()))
+                .and_then(|v| serde_json::to_value(v).map_err(ExecError::SerializingResultErr))
+        };
+
+        once(self.map(map))
+    }
+}
+
 #[pin_project(project = FutureSerializeResultFutureProj)]
 pub struct FutureSerializeResultFuture<TFut, T>(#[pin] TFut, PhantomData<T>);
 
@@ -128,6 +175,7 @@ where
     T: Serialize + Type,
 {
     type Result = T;
+    type Error = Error;
     type Stream = MapStream<TStream>;
     type Type = StreamMarker;
 
@@ -146,6 +194,7 @@ where
     T: Serialize + Type,
 {
     type Result = T;
+    type Error = Error;
     type Stream = MapStream<TStream>;
     type Type = StreamMarker;
 
@@ -161,6 +210,27 @@ where
     }
 }
 
+#[doc(hidden)]
+pub enum AlphaTypedResultStreamMarker {}
+impl<TStream, T, TErr> AlphaRequestLayer<AlphaTypedResultStreamMarker>
+    for Typed<Result<TStream, TErr>>
+where
+    TStream: Stream<Item = T> + Send + Sync + 'static,
+    T: Serialize + Type,
+    TErr: ResolverError,
+{
+    type Result = T;
+    type Error = TErr;
+    type Stream = MapStream<TStream>;
+    type Type = StreamMarker;
+
+    fn exec(self) -> Self::Stream {
+        AlphaRequestLayer::<AlphaResultStreamMarker>::exec(
+            self.0.map_err(ResolverError::into_resolver_error),
+        )
+    }
+}
+
 #[doc(hidden)]
 pub enum AlphaFutureStreamMarker {}
 impl<TFut, TStream, T> AlphaRequestLayer<AlphaFutureStreamMarker> for TFut
@@ -170,6 +240,7 @@ where
     T: Serialize + Type,
 {
     type Result = T;
+    type Error = Error;
     type Stream = FutureMapStream<TFut, TStream>;
     type Type = StreamMarker;
 
@@ -193,6 +264,7 @@ where
     T: Serialize + Type,
 {
     type Result = T;
+    type Error = Error;
     type Stream = FutureMapStream<TFut, TStream>;
     type Type = StreamMarker;
 
@@ -207,6 +279,33 @@ where
     }
 }
 
+#[doc(hidden)]
+pub enum AlphaFutureTypedResultStreamMarker {}
+impl<TFut, TStream, T, TErr> AlphaRequestLayer<AlphaFutureTypedResultStreamMarker> for TFut
+where
+    TFut: Future<Output = Typed<Result<TStream, TErr>>> + Send + 'static,
+    TStream: Stream<Item = T> + Send + Sync + 'static,
+    T: Serialize + Type,
+    TErr: ResolverError,
+{
+    type Result = T;
+    type Error = TErr;
+    type Stream = FutureMapStream<TFut, TStream>;
+    type Type = StreamMarker;
+
+    fn exec(self) -> Self::Stream {
+        FutureMapStream(
+            None,
+            PinnedOption::Some(self),
+            PinnedOption::None,
+            |s| {
+                s.0.map_err(|err| ExecError::ErrResolverError(err.into_resolver_error()))
+            },
+            |v| serde_json::to_value(v).map_err(ExecError::SerializingResultErr),
+        )
+    }
+}
+
 #[pin_project(project = MapStreamProj)]
 pub struct MapStream<S: Stream>(
     Option<ExecError>,
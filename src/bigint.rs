@@ -0,0 +1,198 @@
+use serde_json::{Number, Value};
+use specta::{
+    datatype::{EnumRepr, Fields, PrimitiveType},
+    DataType, TypeMap,
+};
+use specta_typescript::BigIntExportBehavior;
+
+use crate::{
+    internal::{jsonrpc::JsonRPCError, ProcedureDataType, ProcedureKind},
+    Error, ExecError, Router,
+};
+
+impl<TCtx, TMeta> Router<TCtx, TMeta>
+where
+    TCtx: Send + 'static,
+{
+    /// convert the 64 and 128 bit integers in the result of a procedure to strings when they are exported as `string` in the Typescript bindings.
+    pub(crate) fn bigints_to_strings(&self, kind: &ProcedureKind, key: &str, result: &mut Value) {
+        if let Some(dt) = self.bigint_datatype(kind, key, |ty| &ty.result) {
+            convert(dt, &self.typ_store, result, true);
+        }
+    }
+
+    /// the inverse of [Router::bigints_to_strings] for the input of a procedure so it can be deserialized into the Rust types.
+    pub(crate) fn bigints_from_strings(&self, kind: &ProcedureKind, key: &str, input: &mut Value) {
+        if let Some(dt) = self.bigint_datatype(kind, key, |ty| &ty.input) {
+            convert(dt, &self.typ_store, input, false);
+        }
+    }
+
+    /// convert an error of a procedure to a JSON-RPC error. The integers in the typed error are converted like [Router::bigints_to_strings].
+    pub(crate) fn procedure_error_to_jsonrpc(
+        &self,
+        kind: &ProcedureKind,
+        key: &str,
+        err: ExecError,
+    ) -> JsonRPCError {
+        let mut err = Error::from(err);
+        if let (Some(dt), Some(data)) = (
+            self.bigint_datatype(kind, key, |ty| &ty.error),
+            &mut err.data,
+        ) {
+            convert(dt, &self.typ_store, data, true);
+        }
+        err.into_jsonrpc(&self.config)
+    }
+
+    fn bigint_datatype(
+        &self,
+        kind: &ProcedureKind,
+        key: &str,
+        f: impl FnOnce(&ProcedureDataType) -> &DataType,
+    ) -> Option<&DataType> {
+        if !matches!(self.config.ts_bigint, BigIntExportBehavior::String) {
+            return None;
+        }
+
+        let store = match kind {
+            ProcedureKind::Query => &self.queries,
+            ProcedureKind::Mutation => &self.mutations,
+            ProcedureKind::Subscription => &self.subscriptions,
+        };
+        store.store.get(key).map(|procedure| f(&procedure.ty))
+    }
+}
+
+fn convert(dt: &DataType, type_map: &TypeMap, value: &mut Value, to_string: bool) {
+    match dt {
+        DataType::Primitive(
+            PrimitiveType::i64
+            | PrimitiveType::i128
+            | PrimitiveType::isize
+            | PrimitiveType::u64
+            | PrimitiveType::u128
+            | PrimitiveType::usize,
+        ) => match value {
+            Value::Number(n) if to_string => *value = Value::String(n.to_string()),
+            Value::String(s) if !to_string => {
+                // Numbers outside of the 64 bit range need serde_json's `arbitrary_precision` feature. Without it they are left as strings.
+                if let Some(n) = s
+                    .parse::<i128>()
+                    .ok()
+                    .and_then(Number::from_i128)
+                    .or_else(|| s.parse::<u128>().ok().and_then(Number::from_u128))
+                {
+                    *value = Value::Number(n);
+                }
+            }
+            _ => {}
+        },
+        DataType::Nullable(inner) => convert(inner, type_map, value, to_string),
+        DataType::List(list) => {
+            if let Value::Array(items) = value {
+                for item in items {
+                    convert(list.ty(), type_map, item, to_string);
+                }
+            }
+        }
+        DataType::Map(map) => {
+            if let Value::Object(entries) = value {
+                for (_, entry) in entries {
+                    convert(map.value_ty(), type_map, entry, to_string);
+                }
+            }
+        }
+        DataType::Struct(s) => convert_fields(s.fields(), type_map, value, to_string),
+        DataType::Enum(e) => {
+            let variant = |name: &str| {
+                e.variants()
+                    .iter()
+                    .find(|(variant, _)| &**variant == name)
+                    .map(|(_, variant)| variant.fields())
+            };
+
+            match e.repr() {
+                EnumRepr::External => {
+                    if let Value::Object(map) = value {
+                        for (name, value) in map {
+                            if let Some(fields) = variant(name.as_str()) {
+                                convert_fields(fields, type_map, value, to_string);
+                            }
+                        }
+                    }
+                }
+                EnumRepr::Internal { tag } => {
+                    if let Some(fields) =
+                        value.get(&**tag).and_then(Value::as_str).and_then(variant)
+                    {
+                        convert_fields(fields, type_map, value, to_string);
+                    }
+                }
+                EnumRepr::Adjacent { tag, content } => {
+                    if let Some(fields) =
+                        value.get(&**tag).and_then(Value::as_str).and_then(variant)
+                    {
+                        if let Some(value) = value.get_mut(&**content) {
+                            convert_fields(fields, type_map, value, to_string);
+                        }
+                    }
+                }
+                // TODO: Work out which variant an untagged enum was deserialized from
+                EnumRepr::Untagged => {}
+            }
+        }
+        DataType::Tuple(tuple) => {
+            if let Value::Array(items) = value {
+                for (dt, item) in tuple.elements().iter().zip(items) {
+                    convert(dt, type_map, item, to_string);
+                }
+            }
+        }
+        // TODO: Generic arguments are not applied so integers in generic fields are left as is.
+        DataType::Reference(reference) => {
+            if let Some(dt) = type_map.get(reference.sid()) {
+                convert(&dt.inner, type_map, value, to_string);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn convert_fields(fields: &Fields, type_map: &TypeMap, value: &mut Value, to_string: bool) {
+    match fields {
+        Fields::Unit => {}
+        Fields::Unnamed(fields) => {
+            let types = fields
+                .fields()
+                .iter()
+                .filter_map(|field| field.ty())
+                .collect::<Vec<_>>();
+
+            // A newtype is serialized as it's inner value.
+            match (types.as_slice(), value) {
+                ([dt], value) => convert(dt, type_map, value, to_string),
+                (types, Value::Array(items)) => {
+                    for (dt, item) in types.iter().zip(items) {
+                        convert(dt, type_map, item, to_string);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Fields::Named(fields) => {
+            for (name, field) in fields.fields() {
+                // Fields which are `#[serde(skip)]` don't have a type.
+                let Some(dt) = field.ty() else {
+                    continue;
+                };
+
+                if field.flatten() {
+                    convert(dt, type_map, value, to_string);
+                } else if let Some(value) = value.get_mut(&**name) {
+                    convert(dt, type_map, value, to_string);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::error;
+
+use serde_json::Value;
+
+/// A wire format for the messages sent between the client and the server.
+///
+/// JSON is always supported. Other codecs are added with [Config::codec](crate::Config::codec) and the client picks one
+/// with the `Content-Type` and `Accept` headers of an HTTP request or the subprotocol of a WebSocket.
+/// Messages are encoded from and decoded into [Value] so a codec only needs to support the JSON data model.
+/// Codecs only apply to the HTTP and WebSocket transports. The Tauri integration only supports JSON and panics if a codec was added.
+pub trait Codec: Send + Sync + 'static {
+    /// the MIME type of the encoded messages. Eg. `application/msgpack`.
+    fn content_type(&self) -> &'static str;
+
+    /// the WebSocket subprotocol which selects this codec. Eg. `rspc.msgpack`.
+    fn subprotocol(&self) -> &'static str;
+
+    /// whether the encoded messages are sent as binary WebSocket messages instead of text.
+    fn is_binary(&self) -> bool;
+
+    /// encode a message.
+    fn encode(&self, value: &Value) -> Result<Vec<u8>, CodecError>;
+
+    /// decode a message.
+    fn decode(&self, bytes: &[u8]) -> Result<Value, CodecError>;
+}
+
+/// An error encoding or decoding a message with a [Codec].
+#[derive(thiserror::Error, Debug)]
+#[error("{0}")]
+pub struct CodecError(Box<dyn error::Error + Send + Sync>);
+
+impl CodecError {
+    pub fn new<TErr>(err: TErr) -> Self
+    where
+        TErr: error::Error + Send + Sync + 'static,
+    {
+        Self(Box::new(err))
+    }
+}
+
+/// The JSON codec. This is used when the client doesn't ask for another one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn subprotocol(&self) -> &'static str {
+        "rspc.json"
+    }
+
+    fn is_binary(&self) -> bool {
+        false
+    }
+
+    fn encode(&self, value: &Value) -> Result<Vec<u8>, CodecError> {
+        serde_json::to_vec(value).map_err(CodecError::new)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value, CodecError> {
+        serde_json::from_slice(bytes).map_err(CodecError::new)
+    }
+}
+
+/// The [MessagePack](https://msgpack.org) codec.
+#[cfg(feature = "msgpack")]
+#[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgPackCodec;
+
+#[cfg(feature = "msgpack")]
+impl Codec for MsgPackCodec {
+    fn content_type(&self) -> &'static str {
+        "application/msgpack"
+    }
+
+    fn subprotocol(&self) -> &'static str {
+        "rspc.msgpack"
+    }
+
+    fn is_binary(&self) -> bool {
+        true
+    }
+
+    fn encode(&self, value: &Value) -> Result<Vec<u8>, CodecError> {
+        rmp_serde::to_vec_named(value).map_err(CodecError::new)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value, CodecError> {
+        rmp_serde::from_slice(bytes).map_err(CodecError::new)
+    }
+}
+
+/// The [CBOR](https://cbor.io) codec.
+#[cfg(feature = "cbor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborCodec;
+
+#[cfg(feature = "cbor")]
+impl Codec for CborCodec {
+    fn content_type(&self) -> &'static str {
+        "application/cbor"
+    }
+
+    fn subprotocol(&self) -> &'static str {
+        "rspc.cbor"
+    }
+
+    fn is_binary(&self) -> bool {
+        true
+    }
+
+    fn encode(&self, value: &Value) -> Result<Vec<u8>, CodecError> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(value, &mut buf).map_err(CodecError::new)?;
+        Ok(buf)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value, CodecError> {
+        ciborium::from_reader(bytes).map_err(CodecError::new)
+    }
+}
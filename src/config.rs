@@ -1,10 +1,58 @@
-use std::path::PathBuf;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use specta_typescript::{BigIntExportBehavior, CommentFormatterFn};
+
+use crate::{Codec, Cors};
+
+/// What to do when a subscription produces events faster than they can be sent to the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackpressurePolicy {
+    /// Stop polling the subscription's stream until there is room in the buffer.
+    #[default]
+    Block,
+    /// Drop the oldest buffered event to make room for the new one.
+    DropOldest,
+    /// Only keep the most recent event, replacing any events which haven't been sent yet.
+    LatestOnly,
+    /// Stop the subscription and send the client an error.
+    CloseWithError,
+}
+
+/// How the types are declared in the exported Typescript bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TsDeclarationStyle {
+    /// `export type MyType = ...`
+    #[default]
+    Export,
+    /// `declare type MyType = ...`. This is useful when the bindings are a `.d.ts` file.
+    Declare,
+}
 
 /// TODO
 pub struct Config {
     pub(crate) expose_errors: bool,
     pub(crate) export_bindings_on_build: Option<PathBuf>,
     pub(crate) bindings_header: Option<&'static str>,
+    pub(crate) zod_schemas: bool,
+    pub(crate) ts_client: bool,
+    pub(crate) ts_split: bool,
+    pub(crate) ts_bigint: BigIntExportBehavior,
+    pub(crate) ts_formatter: Option<fn(&Path) -> io::Result<()>>,
+    pub(crate) ts_comment_style: Option<CommentFormatterFn>,
+    pub(crate) ts_declaration_style: TsDeclarationStyle,
+    pub(crate) batch_concurrency_limit: Option<usize>,
+    pub(crate) websocket_concurrency_limit: usize,
+    pub(crate) default_timeout: Option<Duration>,
+    pub(crate) subscription_buffer_size: usize,
+    pub(crate) backpressure_policy: BackpressurePolicy,
+    pub(crate) sse_heartbeat_interval: Duration,
+    pub(crate) cors: Option<Cors>,
+    pub(crate) codecs: Vec<Arc<dyn Codec>>,
 }
 
 impl Default for Config {
@@ -19,6 +67,21 @@ impl Config {
             expose_errors: false,
             export_bindings_on_build: None,
             bindings_header: None,
+            zod_schemas: false,
+            ts_client: false,
+            ts_split: false,
+            ts_bigint: BigIntExportBehavior::BigInt,
+            ts_formatter: None,
+            ts_comment_style: None,
+            ts_declaration_style: TsDeclarationStyle::Export,
+            batch_concurrency_limit: None,
+            websocket_concurrency_limit: 16,
+            default_timeout: None,
+            subscription_buffer_size: 100,
+            backpressure_policy: BackpressurePolicy::Block,
+            sse_heartbeat_interval: Duration::from_secs(15),
+            cors: None,
+            codecs: Vec::new(),
         }
     }
 
@@ -39,7 +102,112 @@ impl Config {
         self
     }
 
-    /// expose the errors to the frontend (in the `error` field of the response).
+    /// sets how 64 and 128 bit integers are exported in the Typescript bindings. Defaults to [`BigIntExportBehavior::BigInt`].
+    /// With [`BigIntExportBehavior::String`] these integers are also sent to and received from the frontend as strings so the runtime values match the bindings.
+    pub fn ts_bigint(mut self, behavior: BigIntExportBehavior) -> Self {
+        self.ts_bigint = behavior;
+        self
+    }
+
+    /// runs a formatter on the exported Typescript bindings. Eg. `specta_typescript::formatter::prettier`.
+    pub fn ts_formatter(mut self, formatter: fn(&Path) -> io::Result<()>) -> Self {
+        self.ts_formatter = Some(formatter);
+        self
+    }
+
+    /// sets how doc comments on your types are exported in the Typescript bindings. Eg. `specta_typescript::comments::js_doc`.
+    pub fn ts_comment_style(mut self, style: CommentFormatterFn) -> Self {
+        self.ts_comment_style = Some(style);
+        self
+    }
+
+    /// sets whether the types in the Typescript bindings are declared with `export type` or `declare type`. Defaults to [`TsDeclarationStyle::Export`].
+    /// Exporting fails if [`TsDeclarationStyle::Declare`] is combined with [Config::export_zod_schemas] or [Config::export_ts_client] as they export runtime code.
+    pub fn ts_declaration_style(mut self, style: TsDeclarationStyle) -> Self {
+        self.ts_declaration_style = style;
+        self
+    }
+
+    /// adds a Zod schema for every type and procedure to the exported Typescript bindings.
+    /// The bindings will import `z` from the `zod` package so it must be installed in your frontend.
+    pub fn export_zod_schemas(mut self) -> Self {
+        self.zod_schemas = true;
+        self
+    }
+
+    /// adds a `createApi` function to the exported Typescript bindings which wraps an `AlphaClient` from `@rspc/client`.
+    /// It returns an object with one typed function per procedure nested by the dotted prefixes of the keys, Eg. `api.users.get(input)`.
+    pub fn export_ts_client(mut self) -> Self {
+        self.ts_client = true;
+        self
+    }
+
+    /// splits the Typescript bindings into multiple files. The export path is used as a directory containing a `types.ts` with every type,
+    /// a file in `procedures/` for each prefix routers were merged with and an `index.ts` which re-exports everything.
+    /// Routers merged into a merged router get their own file, Eg. `procedures/users.admin.ts`, and the files of prefixes which no longer exist are removed.
+    /// Exporting fails if this is combined with [TsDeclarationStyle::Declare] or if two prefixes map to the same file name.
+    pub fn split_ts_bindings(mut self) -> Self {
+        self.ts_split = true;
+        self
+    }
+
+    /// limits how many requests from a single batch are executed at the same time.
+    /// By default every request in a batch is executed concurrently. The responses are always returned in request order.
+    pub fn batch_concurrency_limit(mut self, limit: usize) -> Self {
+        self.batch_concurrency_limit = Some(limit.max(1));
+        self
+    }
+
+    /// limits how many queries and mutations can be in-flight at the same time on a single WebSocket connection.
+    /// Once the limit is reached no further messages are read from the connection until a running request finishes. Defaults to `16`.
+    pub fn websocket_concurrency_limit(mut self, limit: usize) -> Self {
+        self.websocket_concurrency_limit = limit.max(1);
+        self
+    }
+
+    /// sets the timeout for every procedure which doesn't have one set on itself or on the router it was defined in.
+    /// When a procedure runs longer than this it is dropped and the client receives a `Timeout` error.
+    /// For subscriptions this only applies to setting up the stream, not to the events it yields.
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// sets how many events of a single subscription can be waiting to be sent to the client. Defaults to `100`.
+    /// This is also used as the size of the outgoing message buffer of a WebSocket connection.
+    pub fn subscription_buffer_size(mut self, size: usize) -> Self {
+        self.subscription_buffer_size = size.max(1);
+        self
+    }
+
+    /// sets what happens when a subscription's buffer is full. Defaults to [`BackpressurePolicy::Block`].
+    pub fn backpressure_policy(mut self, policy: BackpressurePolicy) -> Self {
+        self.backpressure_policy = policy;
+        self
+    }
+
+    /// sets how often a heartbeat comment is sent on an idle Server-Sent Events subscription. Defaults to 15 seconds.
+    /// This stops proxies from closing the connection while no events are being produced. A zero interval disables the heartbeat.
+    pub fn sse_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.sse_heartbeat_interval = interval;
+        self
+    }
+
+    /// enables CORS on the HTTP endpoint so it can be called from a frontend on another origin. Refer to [Cors] for what is allowed.
+    pub fn cors(mut self, cors: Cors) -> Self {
+        self.cors = Some(cors);
+        self
+    }
+
+    /// adds a wire format the client can choose instead of JSON, Eg. [MsgPackCodec](crate::MsgPackCodec) with the `msgpack` feature.
+    /// It is selected by the `Content-Type` and `Accept` headers of HTTP requests and by the subprotocol of WebSockets.
+    /// The Tauri integration only supports JSON so it panics if a codec was added.
+    pub fn codec(mut self, codec: impl Codec) -> Self {
+        self.codecs.push(Arc::new(codec));
+        self
+    }
+
+    /// expose the errors to the frontend. The message of every error in the cause chain is put into the `data` field of the error response.
     /// This will also certainly likely leak information about your backend, so use with caution.
     pub fn expose_errors(mut self) -> Self {
         self.expose_errors = true;
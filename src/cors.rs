@@ -0,0 +1,94 @@
+use std::{borrow::Cow, time::Duration};
+
+/// Cross-Origin Resource Sharing (CORS) settings for the HTTP endpoint of a router. Set them with [Config::cors](crate::Config::cors).
+///
+/// Preflight `OPTIONS` requests are answered by the endpoint and the CORS headers are added to every response, including WebSocket upgrades.
+/// Browsers don't apply CORS to WebSockets so upgrades from an origin which isn't allowed are rejected.
+#[derive(Debug, Clone)]
+pub struct Cors {
+    pub(crate) origins: Option<Vec<Cow<'static, str>>>,
+    pub(crate) methods: Vec<Cow<'static, str>>,
+    pub(crate) headers: Option<Vec<Cow<'static, str>>>,
+    pub(crate) credentials: bool,
+    pub(crate) max_age: Option<Duration>,
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Cors {
+    /// create CORS settings which don't allow any origins. Allow them with [Cors::allow_origin] or [Cors::allow_any_origin].
+    /// By default the `GET` and `POST` methods and the `Content-Type` header are allowed.
+    pub fn new() -> Self {
+        Self {
+            origins: Some(Vec::new()),
+            methods: vec!["GET".into(), "POST".into()],
+            headers: Some(vec!["Content-Type".into()]),
+            credentials: false,
+            max_age: None,
+        }
+    }
+
+    /// allows requests from an origin. Eg. `https://app.example.com`.
+    pub fn allow_origin(mut self, origin: impl Into<Cow<'static, str>>) -> Self {
+        if let Some(origins) = &mut self.origins {
+            origins.push(origin.into());
+        }
+        self
+    }
+
+    /// allows requests from every origin.
+    pub fn allow_any_origin(mut self) -> Self {
+        self.origins = None;
+        self
+    }
+
+    /// sets the methods a cross-origin request can use.
+    pub fn allow_methods<I, M>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = M>,
+        M: Into<Cow<'static, str>>,
+    {
+        self.methods = methods.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// sets the headers a cross-origin request can send.
+    pub fn allow_headers<I, H>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<Cow<'static, str>>,
+    {
+        self.headers = Some(headers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// allows a cross-origin request to send any headers.
+    pub fn allow_any_header(mut self) -> Self {
+        self.headers = None;
+        self
+    }
+
+    /// allows cross-origin requests to include cookies and the `Authorization` header.
+    /// The origin of the request is sent back instead of `*` as browsers don't allow credentials with a wildcard origin.
+    pub fn allow_credentials(mut self) -> Self {
+        self.credentials = true;
+        self
+    }
+
+    /// sets how long the browser can cache the result of a preflight request.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub(crate) fn allows_origin(&self, origin: &str) -> bool {
+        match &self.origins {
+            Some(origins) => origins.iter().any(|o| o.eq_ignore_ascii_case(origin)),
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+/// Turn doc comments into a string for [BuiltProcedureBuilder::description](crate::internal::BuiltProcedureBuilder::description).
+///
+/// This allows a procedure to be documented with regular Rust doc comments which are exported as JSDoc in the Typescript bindings.
+///
+/// ```rust
+/// let router = rspc::Router::<()>::new()
+///     .query("version", |t| {
+///         t(|_, _: ()| Ok(env!("CARGO_PKG_VERSION"))).description(rspc::docs! {
+///             /// The version of the server.
+///             ///
+///             /// This follows semver.
+///         })
+///     })
+///     .build();
+///
+/// let procedure = router.procedures().next().unwrap();
+/// assert_eq!(
+///     procedure.description(),
+///     Some("The version of the server.\n\nThis follows semver.")
+/// );
+/// ```
+#[macro_export]
+macro_rules! docs {
+    ($(#[doc = $doc:expr])*) => {
+        $crate::internal::doc_comment(&[$($doc),*])
+    };
+}
@@ -1,9 +1,10 @@
-use std::{error, fmt, sync::Arc};
+use std::{error, fmt, path::PathBuf, sync::Arc};
 
 use serde::Serialize;
-use specta::Type;
+use serde_json::{json, Value};
+use specta::{DataType, Type, TypeMap};
 
-use crate::internal::jsonrpc::JsonRPCError;
+use crate::{internal::jsonrpc::JsonRPCError, Config};
 
 #[derive(thiserror::Error, Debug)]
 pub enum ExecError {
@@ -13,6 +14,10 @@ pub enum ExecError {
     DeserializingArgErr(serde_json::Error),
     #[error("error serializing procedure result: {0}")]
     SerializingResultErr(serde_json::Error),
+    #[error("error serializing procedure arguments: {0}")]
+    SerializingArgErr(serde_json::Error),
+    #[error("error deserializing procedure result: {0}")]
+    DeserializingResultErr(serde_json::Error),
     #[cfg(feature = "axum")]
     #[error("error in axum extractor")]
     AxumExtractorError,
@@ -26,6 +31,12 @@ pub enum ExecError {
     ErrSubscriptionWithNullId,
     #[error("error creating subscription with duplicate id")]
     ErrSubscriptionDuplicateId,
+    #[error("the request was cancelled by the client")]
+    ErrRequestCancelled,
+    #[error("the procedure did not complete within its timeout")]
+    ErrTimeout,
+    #[error("the subscription was closed as the client couldn't keep up with its events")]
+    ErrSubscriptionBufferFull,
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -37,53 +48,100 @@ impl From<ExecError> for Error {
                 code: ErrorCode::NotFound,
                 message: "the requested operation is not supported by this server".to_string(),
                 cause: None,
+                data: None,
             },
             ExecError::DeserializingArgErr(err) => Error {
                 code: ErrorCode::BadRequest,
                 message: "error deserializing procedure arguments".to_string(),
                 cause: Some(Arc::new(err)),
+                data: None,
             },
             ExecError::SerializingResultErr(err) => Error {
                 code: ErrorCode::InternalServerError,
                 message: "error serializing procedure result".to_string(),
                 cause: Some(Arc::new(err)),
+                data: None,
+            },
+            ExecError::SerializingArgErr(err) => Error {
+                code: ErrorCode::InternalServerError,
+                message: "error serializing procedure arguments".to_string(),
+                cause: Some(Arc::new(err)),
+                data: None,
+            },
+            ExecError::DeserializingResultErr(err) => Error {
+                code: ErrorCode::InternalServerError,
+                message: "error deserializing procedure result".to_string(),
+                cause: Some(Arc::new(err)),
+                data: None,
             },
             #[cfg(feature = "axum")]
             ExecError::AxumExtractorError => Error {
                 code: ErrorCode::BadRequest,
                 message: "Error running Axum extractors on the HTTP request".into(),
                 cause: None,
+                data: None,
             },
             ExecError::InvalidJsonRpcVersion => Error {
                 code: ErrorCode::BadRequest,
                 message: "invalid JSON-RPC version".into(),
                 cause: None,
+                data: None,
             },
             ExecError::ErrResolverError(err) => err,
             ExecError::UnsupportedMethod(_) => Error {
                 code: ErrorCode::BadRequest,
                 message: "unsupported metho".into(),
                 cause: None,
+                data: None,
             },
             ExecError::ErrSubscriptionWithNullId => Error {
                 code: ErrorCode::BadRequest,
                 message: "error creating subscription with null request id".into(),
                 cause: None,
+                data: None,
             },
             ExecError::ErrSubscriptionDuplicateId => Error {
                 code: ErrorCode::BadRequest,
                 message: "error creating subscription with duplicate id".into(),
                 cause: None,
+                data: None,
+            },
+            ExecError::ErrRequestCancelled => Error {
+                code: ErrorCode::ClientClosedRequest,
+                message: "the request was cancelled by the client".into(),
+                cause: None,
+                data: None,
+            },
+            ExecError::ErrTimeout => Error {
+                code: ErrorCode::Timeout,
+                message: "the procedure did not complete within its timeout".into(),
+                cause: None,
+                data: None,
+            },
+            ExecError::ErrSubscriptionBufferFull => Error {
+                code: ErrorCode::InternalServerError,
+                message:
+                    "the subscription was closed as the client couldn't keep up with its events"
+                        .into(),
+                cause: None,
+                data: None,
             },
             ExecError::Internal(msg) => Error {
                 code: ErrorCode::InternalServerError,
                 message: msg,
                 cause: None,
+                data: None,
             },
         }
     }
 }
 
+impl ExecError {
+    pub(crate) fn into_jsonrpc(self, config: &Config) -> JsonRPCError {
+        Error::from(self).into_jsonrpc(config)
+    }
+}
+
 impl From<ExecError> for JsonRPCError {
     fn from(err: ExecError) -> Self {
         let x: Error = err.into();
@@ -97,15 +155,34 @@ pub enum ExportError {
     IOErr(#[from] std::io::Error),
     #[error("error exporting typescript bindings: {0}")]
     TsExportErr(#[from] specta_typescript::ExportError),
+    #[error("error serializing exported schema: {0}")]
+    SerializeErr(#[from] serde_json::Error),
+    #[error("procedure '{0}' conflicts with another procedure in the generated client. Keys must be unique and can't be a prefix of another key")]
+    ClientKeyConflict(String),
+    #[error("`TsDeclarationStyle::Declare` can't be combined with Zod schemas or the client as they export runtime code")]
+    DeclareStyleRuntimeExport,
+    #[error("split bindings can't use `TsDeclarationStyle::Declare` as the files import types from each other")]
+    SplitDeclareStyle,
+    #[error("the prefixes '{0}' and '{1}' conflict in the split bindings as they map to the same file or type name. Rename one of them")]
+    SplitNamespaceConflict(String, String),
+    #[error("bindings at '{}' are out of date. Line {line} should be `{expected}` but found `{found}`", path.display())]
+    BindingsOutdated {
+        path: PathBuf,
+        line: usize,
+        expected: String,
+        found: String,
+    },
+    #[error("bindings at '{}' are no longer generated. Run `Router::export_ts` to remove them", path.display())]
+    BindingsOrphaned { path: PathBuf },
 }
 
-#[derive(Debug, Clone, Serialize, Type)]
+#[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct Error {
     pub(crate) code: ErrorCode,
     pub(crate) message: String,
-    #[serde(skip)]
     pub(crate) cause: Option<Arc<dyn std::error::Error + Send + Sync>>, // We are using `Arc` instead of `Box` so we can clone the error cause `Clone` isn't dyn safe.
+    pub(crate) data: Option<Value>, // The serialized typed error of the procedure. Refer to [ResolverError].
 }
 
 impl From<Error> for JsonRPCError {
@@ -113,7 +190,7 @@ impl From<Error> for JsonRPCError {
         JsonRPCError {
             code: err.code.to_status_code() as i32,
             message: err.message,
-            data: None,
+            data: err.data,
         }
     }
 }
@@ -130,16 +207,43 @@ impl fmt::Display for Error {
 
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        None
+        self.cause
+            .as_ref()
+            .map(|cause| cause.as_ref() as &(dyn error::Error + 'static))
     }
 }
 
 impl Error {
+    // Unlike the `From` implementation this will include the messages of the cause chain in `data` when `Config::expose_errors` is enabled.
+    // A typed error is always sent as-is so it matches the exported type of the procedure.
+    pub(crate) fn into_jsonrpc(self, config: &Config) -> JsonRPCError {
+        let data = match (config.expose_errors, &self.cause, &self.data) {
+            (_, _, Some(data)) => Some(data.clone()),
+            (true, Some(cause), None) => {
+                let mut causes = Vec::new();
+                let mut next = Some(cause.as_ref() as &(dyn error::Error + 'static));
+                while let Some(err) = next {
+                    causes.push(Value::String(err.to_string()));
+                    next = err.source();
+                }
+
+                Some(json!({ "causes": causes }))
+            }
+            _ => None,
+        };
+
+        JsonRPCError {
+            data,
+            ..self.into()
+        }
+    }
+
     pub const fn new(code: ErrorCode, message: String) -> Self {
         Error {
             code,
             message,
             cause: None,
+            data: None,
         }
     }
 
@@ -151,10 +255,81 @@ impl Error {
             code,
             message,
             cause: Some(Arc::new(cause)),
+            data: None,
         }
     }
 }
 
+/// An error which can be returned from a resolver.
+///
+/// This is implemented for [Error] and for your own error types which implement `Serialize + Type + Into<Error>`.
+/// A custom error is serialized into the `data` field of the error response and it's type is exported with the procedure.
+/// Resolvers must wrap their result in [Typed] to return a custom error.
+pub trait ResolverError: Send + 'static {
+    #[doc(hidden)]
+    fn into_resolver_error(self) -> Error;
+
+    #[doc(hidden)]
+    fn typedef(type_map: &mut TypeMap) -> DataType;
+}
+
+impl ResolverError for Error {
+    fn into_resolver_error(self) -> Error {
+        self
+    }
+
+    fn typedef(type_map: &mut TypeMap) -> DataType {
+        // An empty tuple is exported as `never` as these errors don't carry any typed data.
+        <() as Type>::reference(type_map, &[]).inner
+    }
+}
+
+impl<TErr> ResolverError for TErr
+where
+    TErr: Serialize + Type + Into<Error> + Send + 'static,
+{
+    fn into_resolver_error(self) -> Error {
+        let data = serde_json::to_value(&self)
+            .map_err(|_err| {
+                tracing::error!("Error serializing resolver error: {}", _err);
+            })
+            .ok();
+
+        Error {
+            data,
+            ..self.into()
+        }
+    }
+
+    fn typedef(type_map: &mut TypeMap) -> DataType {
+        <TErr as Type>::reference(type_map, &[]).inner
+    }
+}
+
+/// Wraps the result of a resolver which returns a custom [ResolverError].
+///
+/// Resolvers returning `Result<T, Error>` don't need this. Requiring it for custom errors means `Ok(..)` and `?` still infer [Error] as the error type of those resolvers.
+///
+/// ```rust
+/// # use rspc::{Error, ErrorCode, Typed};
+/// #[derive(serde::Serialize, specta::Type)]
+/// enum LoginError {
+///     InvalidPassword,
+/// }
+///
+/// impl From<LoginError> for Error {
+///     fn from(_: LoginError) -> Self {
+///         Error::new(ErrorCode::Unauthorized, "invalid password".into())
+///     }
+/// }
+///
+/// fn login(password: String) -> Typed<Result<(), LoginError>> {
+///     Typed(if password == "hunter2" { Ok(()) } else { Err(LoginError::InvalidPassword) })
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Typed<T>(pub T);
+
 /// TODO
 #[derive(Debug, Clone, Serialize, Type, PartialEq, Eq)]
 pub enum ErrorCode {
@@ -1,20 +1,31 @@
-use futures::{SinkExt, StreamExt};
+use futures::{stream, FutureExt, SinkExt, StreamExt};
 use futures_channel::mpsc;
 use httpz::{
     axum::axum::extract::FromRequestParts,
-    http::{self, Method, Response, StatusCode},
-    ws::{Message, WebsocketUpgrade},
+    http::{self, HeaderValue, Method, Response, StatusCode},
+    ws::{Message, Websocket, WebsocketUpgrade},
     Endpoint, GenericEndpoint, HttpEndpoint, HttpResponse,
 };
+use serde::Serialize;
 use serde_json::Value;
-use std::{borrow::Cow, collections::HashMap, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    future::{pending, ready, Future},
+    panic::AssertUnwindSafe,
+    sync::Arc,
+};
+use tokio::sync::{oneshot, Semaphore};
 
 use crate::{
     internal::{
-        jsonrpc::{self, handle_json_rpc, RequestId, SubscriptionSender},
+        jsonrpc::{
+            self, handle_json_rpc, handle_json_rpc_with_cancel, OwnedMpscSender, RequestId,
+            RequestInner, ResponseInner, SubscriptionSender,
+        },
         ProcedureKind,
     },
-    Router,
+    Codec, CodecError, Config, Error, ErrorCode, ExecError, JsonCodec, Router,
 };
 
 pub use super::httpz_extractors::*;
@@ -109,39 +120,106 @@ where
     ) -> Endpoint<impl HttpEndpoint> {
         GenericEndpoint::new(
             "/:id", // TODO: I think this is Axum specific. Fix in `httpz`!
-            [Method::GET, Method::POST],
+            [Method::GET, Method::POST, Method::OPTIONS],
             move |req: httpz::Request| {
                 // TODO: It would be nice if these clones weren't per request.
                 // TODO: Maybe httpz can `Box::leak` a ref to a context type and allow it to be shared.
-                let router = self.clone();
-                let ctx_fn = ctx_fn.clone();
+                route(
+                    req,
+                    self.clone(),
+                    ctx_fn.clone(),
+                    |ctx_fn, req, router| handle_websocket(ctx_fn, req, router).into_response(),
+                    |ctx_fn, req, router| async move {
+                        handle_http_batch(ctx_fn, req, &router)
+                            .await
+                            .into_response()
+                    },
+                )
+            },
+        )
+    }
+}
 
-                async move {
-                    match (req.method(), &req.uri().path()[1..]) {
-                        (&Method::GET, "ws") => {
-                            handle_websocket(ctx_fn, req, router).into_response()
-                        }
-                        (&Method::GET, _) => {
-                            handle_http(ctx_fn, ProcedureKind::Query, req, &router)
-                                .await
-                                .into_response()
-                        }
-                        (&Method::POST, "_batch") => handle_http_batch(ctx_fn, req, &router)
+impl<TCtx> Router<TCtx>
+where
+    TCtx: Clone + Send + Sync + 'static,
+{
+    /// Like [Router::endpoint] but the context function is only run once per batch or WebSocket connection.
+    /// The context is cloned for each request within it. Single HTTP requests still run the context function every time.
+    pub fn endpoint_with_shared_ctx<
+        TCtxFnMarker: Send + Sync + 'static,
+        TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>,
+    >(
+        self: Arc<Self>,
+        ctx_fn: TCtxFn,
+    ) -> Endpoint<impl HttpEndpoint> {
+        GenericEndpoint::new(
+            "/:id", // TODO: I think this is Axum specific. Fix in `httpz`!
+            [Method::GET, Method::POST, Method::OPTIONS],
+            move |req: httpz::Request| {
+                route(
+                    req,
+                    self.clone(),
+                    ctx_fn.clone(),
+                    |ctx_fn, req, router| {
+                        handle_websocket_with_shared_ctx(ctx_fn, req, router).into_response()
+                    },
+                    |ctx_fn, req, router| async move {
+                        handle_http_batch_with_shared_ctx(ctx_fn, req, &router)
                             .await
-                            .into_response(),
-                        (&Method::POST, _) => {
-                            handle_http(ctx_fn, ProcedureKind::Mutation, req, &router)
-                                .await
-                                .into_response()
-                        }
-                        _ => unreachable!(),
-                    }
-                }
+                            .into_response()
+                    },
+                )
             },
         )
     }
 }
 
+// The endpoints only differ in how WebSocket connections and batches run the context function so they pass in those handlers.
+async fn route<TCtx, TCtxFn, TCtxFnMarker, TBatchFut>(
+    req: httpz::Request,
+    router: Arc<Router<TCtx>>,
+    ctx_fn: TCtxFn,
+    websocket: impl FnOnce(
+        TCtxFn,
+        httpz::Request,
+        Arc<Router<TCtx>>,
+    ) -> Result<Response<Vec<u8>>, httpz::Error>,
+    batch: impl FnOnce(TCtxFn, httpz::Request, Arc<Router<TCtx>>) -> TBatchFut,
+) -> Result<Response<Vec<u8>>, httpz::Error>
+where
+    TCtx: Send + Sync + 'static,
+    TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>,
+    TBatchFut: Future<Output = Result<Response<Vec<u8>>, httpz::Error>>,
+{
+    let origin = req.headers().get(http::header::ORIGIN).cloned();
+    let resp = match (req.method(), &req.uri().path()[1..]) {
+        (&Method::OPTIONS, _) => handle_cors_preflight(&req, &router.config),
+        (&Method::GET, "ws") if !cors_allows_websocket(&req, &router.config) => error_response(
+            Error::new(
+                ErrorCode::Forbidden,
+                "websocket connections are not allowed from this origin".into(),
+            ),
+            response_codec(&req, &router.config),
+            &router.config,
+        ),
+        (&Method::GET, "ws") => websocket(ctx_fn, req, router.clone()),
+        (&Method::GET, _) if accepts_event_stream(&req) => handle_sse(ctx_fn, req, router.clone())
+            .await
+            .into_response(),
+        (&Method::GET, _) => handle_http(ctx_fn, ProcedureKind::Query, req, &router)
+            .await
+            .into_response(),
+        (&Method::POST, "_batch") => batch(ctx_fn, req, router.clone()).await,
+        (&Method::POST, _) => handle_http(ctx_fn, ProcedureKind::Mutation, req, &router)
+            .await
+            .into_response(),
+        _ => unreachable!(),
+    };
+
+    with_cors_headers(resp, origin.as_ref(), &router.config)
+}
+
 pub async fn handle_http<TCtx, TCtxFn, TCtxFnMarker>(
     ctx_fn: TCtxFn,
     kind: ProcedureKind,
@@ -154,16 +232,18 @@ where
 {
     // Has to be allocated because `TCtxFn` takes ownership of `req`
     let procedure_name = req.uri().path()[1..].to_string();
+    let codec = response_codec(&req, &router.config);
 
+    // The input of a query is always JSON as it's in the URL.
     let input = match *req.method() {
         Method::GET => req
             .query_pairs()
             .and_then(|mut params| params.find(|e| e.0 == "input").map(|e| e.1))
-            .map(|v| serde_json::from_str(&v))
+            .map(|v| serde_json::from_str(&v).map_err(CodecError::new))
             .unwrap_or(Ok(None as Option<Value>)),
         Method::POST => (!req.body().is_empty())
-            .then(|| serde_json::from_slice(req.body()))
-            .unwrap_or(Ok(None)),
+            .then(|| request_codec(&req, &router.config).decode(req.body()))
+            .transpose(),
         _ => unreachable!(),
     };
 
@@ -177,10 +257,15 @@ where
                 _err
             );
 
-            return Ok(Response::builder()
-                .status(StatusCode::NOT_FOUND)
-                .header("Content-Type", "application/json")
-                .body(b"[]".to_vec())?);
+            return error_response(
+                Error::with_cause(
+                    ErrorCode::BadRequest,
+                    "error deserializing procedure arguments".into(),
+                    _err,
+                ),
+                codec,
+                &router.config,
+            );
         }
     };
 
@@ -198,13 +283,7 @@ where
         Err(_err) => {
             tracing::error!("Error executing context function: {}", _err);
 
-            return Ok(
-                Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .header("Content-Type", "application/json")
-                    .body(b"[]".to_vec())?,
-                // TODO: Props just return `None` here so that we don't allocate or need a clone.
-            );
+            return error_response(_err, codec, &router.config);
         }
     };
 
@@ -226,12 +305,14 @@ where
                 ProcedureKind::Subscription => {
                     tracing::error!("Attempted to execute a subscription operation with HTTP");
 
-                    return Ok(Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .header("Content-Type", "application/json")
-                        .body(b"[]".to_vec())?);
+                    return error_response(
+                        ExecError::UnsupportedMethod("subscription".into()),
+                        codec,
+                        &router.config,
+                    );
                 }
             },
+            last_event_id: None,
         },
         Cow::Borrowed(router),
         &mut response,
@@ -239,106 +320,584 @@ where
     .await;
 
     debug_assert!(response.is_some()); // This would indicate a bug in rspc's jsonrpc_exec code
-    let resp = match response {
-        Some(resp) => match serde_json::to_vec(&resp) {
-            Ok(v) => Response::builder()
-                .status(StatusCode::OK)
+    match response {
+        Some(resp) => encode_response(&resp, codec),
+        // This case is unreachable but an error is here just incase.
+        None => error_response(
+            ExecError::Internal("the procedure did not respond".into()),
+            codec,
+            &router.config,
+        ),
+    }
+}
+
+/// respond with a JSON-RPC error. The HTTP status code comes from [ErrorCode::to_status_code](crate::ErrorCode::to_status_code).
+fn error_response(
+    err: impl Into<Error>,
+    codec: &dyn Codec,
+    config: &Config,
+) -> Result<Response<Vec<u8>>, httpz::Error> {
+    encode_response(
+        &jsonrpc::Response {
+            jsonrpc: "2.0",
+            id: RequestId::Null,
+            result: ResponseInner::Error(err.into().into_jsonrpc(config)),
+        },
+        codec,
+    )
+}
+
+/// encode the response to a single request. An error response uses the status code of the error so HTTP caches, proxies and monitoring can tell it failed.
+fn encode_response(
+    resp: &jsonrpc::Response,
+    codec: &dyn Codec,
+) -> Result<Response<Vec<u8>>, httpz::Error> {
+    let status = match &resp.result {
+        ResponseInner::Error(err) => u16::try_from(err.code)
+            .ok()
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        _ => StatusCode::OK,
+    };
+
+    match encode(codec, resp) {
+        Ok(v) => Ok(Response::builder()
+            .status(status)
+            .header("Content-Type", codec.content_type())
+            .body(v)?),
+        Err(_err) => {
+            tracing::error!("Error serializing response: {}", _err);
+
+            Ok(Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
                 .header("Content-Type", "application/json")
-                .body(v)?,
-            Err(_err) => {
-                tracing::error!("Error serializing response: {}", _err);
+                .body(
+                    br#"{"jsonrpc":"2.0","id":null,"result":{"type":"error","data":{"code":500,"message":"error serializing response","data":null}}}"#
+                        .to_vec(),
+                )?)
+        }
+    }
+}
+
+/// answer a preflight `OPTIONS` request with the methods, headers and max age allowed by [Config::cors](crate::Config::cors).
+/// The allowed origin is added by [with_cors_headers] like every other response.
+fn handle_cors_preflight(
+    req: &httpz::Request,
+    config: &Config,
+) -> Result<Response<Vec<u8>>, httpz::Error> {
+    let Some(cors) = &config.cors else {
+        return error_response(
+            Error::new(
+                ErrorCode::MethodNotSupported,
+                "CORS is not enabled on this endpoint".into(),
+            ),
+            &JsonCodec,
+            config,
+        );
+    };
+
+    let mut resp = Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .body(vec![])?;
 
-                Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .header("Content-Type", "application/json")
-                    .body(b"[]".to_vec())?
+    let origin = req
+        .headers()
+        .get(http::header::ORIGIN)
+        .and_then(|v| v.to_str().ok());
+    if !origin.is_some_and(|origin| cors.allows_origin(origin)) {
+        return Ok(resp);
+    }
+
+    let headers = resp.headers_mut();
+    if let Ok(methods) = HeaderValue::from_str(&cors.methods.join(", ")) {
+        headers.insert(http::header::ACCESS_CONTROL_ALLOW_METHODS, methods);
+    }
+    match &cors.headers {
+        Some(allowed) => {
+            if let Ok(allowed) = HeaderValue::from_str(&allowed.join(", ")) {
+                headers.insert(http::header::ACCESS_CONTROL_ALLOW_HEADERS, allowed);
             }
-        },
-        // This case is unreachable but an error is here just incase.
-        None => Response::builder()
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .header("Content-Type", "application/json")
-            .body(b"[]".to_vec())?,
+        }
+        // Allowing any header is done by echoing back the headers the browser asked for.
+        None => {
+            if let Some(requested) = req
+                .headers()
+                .get(http::header::ACCESS_CONTROL_REQUEST_HEADERS)
+            {
+                headers.insert(
+                    http::header::ACCESS_CONTROL_ALLOW_HEADERS,
+                    requested.clone(),
+                );
+            }
+        }
+    }
+    if let Some(max_age) = cors.max_age {
+        headers.insert(
+            http::header::ACCESS_CONTROL_MAX_AGE,
+            HeaderValue::from(max_age.as_secs()),
+        );
+    }
+
+    Ok(resp)
+}
+
+/// add the `Access-Control-Allow-Origin` and `Access-Control-Allow-Credentials` headers to a response if the origin of the request is allowed.
+fn with_cors_headers(
+    resp: Result<Response<Vec<u8>>, httpz::Error>,
+    origin: Option<&HeaderValue>,
+    config: &Config,
+) -> Result<Response<Vec<u8>>, httpz::Error> {
+    let mut resp = resp?;
+    let Some(cors) = &config.cors else {
+        return Ok(resp);
     };
 
+    let headers = resp.headers_mut();
+    // The response depends on the origin unless every origin gets the same `*`.
+    if cors.origins.is_some() || cors.credentials {
+        headers.append(http::header::VARY, HeaderValue::from_static("Origin"));
+    }
+
+    let Some(origin) = origin.filter(|origin| {
+        origin
+            .to_str()
+            .is_ok_and(|origin| cors.allows_origin(origin))
+    }) else {
+        return Ok(resp);
+    };
+
+    match cors.origins.is_none() && !cors.credentials {
+        true => headers.insert(
+            http::header::ACCESS_CONTROL_ALLOW_ORIGIN,
+            HeaderValue::from_static("*"),
+        ),
+        false => headers.insert(http::header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone()),
+    };
+    if cors.credentials {
+        headers.insert(
+            http::header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+            HeaderValue::from_static("true"),
+        );
+    }
+
     Ok(resp)
 }
 
-pub async fn handle_http_batch<TCtx, TCtxFn, TCtxFnMarker>(
+/// Browsers don't apply CORS to WebSockets so the origin must be checked before the connection is upgraded.
+/// Requests without an `Origin` header don't come from a browser so they are always allowed.
+fn cors_allows_websocket(req: &httpz::Request, config: &Config) -> bool {
+    let Some(cors) = &config.cors else {
+        return true;
+    };
+
+    match req.headers().get(http::header::ORIGIN) {
+        Some(origin) => origin
+            .to_str()
+            .is_ok_and(|origin| cors.allows_origin(origin)),
+        None => true,
+    }
+}
+
+/// the codecs added with [Config::codec](crate::Config::codec) followed by JSON.
+fn codecs(config: &Config) -> impl Iterator<Item = &dyn Codec> {
+    config
+        .codecs
+        .iter()
+        .map(|codec| codec.as_ref())
+        .chain(std::iter::once(&JsonCodec as &dyn Codec))
+}
+
+/// find the codec for a MIME type. Parameters like `charset` are ignored.
+fn codec_for_content_type<'a>(config: &'a Config, mime: &str) -> Option<&'a dyn Codec> {
+    let mime = mime.split(';').next().unwrap_or_default().trim();
+    codecs(config).find(|codec| codec.content_type().eq_ignore_ascii_case(mime))
+}
+
+/// the codec of the request body from its `Content-Type` header. JSON is used when it's missing or not supported.
+fn request_codec<'a>(req: &httpz::Request, config: &'a Config) -> &'a dyn Codec {
+    req.headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|mime| codec_for_content_type(config, mime))
+        .unwrap_or(&JsonCodec)
+}
+
+/// the codec of the response from the `Accept` header. The codec of the request body is used when the client didn't ask for a supported one.
+fn response_codec<'a>(req: &httpz::Request, config: &'a Config) -> &'a dyn Codec {
+    req.headers()
+        .get_all(http::header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .find_map(|mime| codec_for_content_type(config, mime))
+        .unwrap_or_else(|| request_codec(req, config))
+}
+
+/// the codec of the first supported subprotocol in the `Sec-WebSocket-Protocol` header. `None` means the client didn't ask for one.
+fn websocket_codec<'a>(req: &httpz::Request, config: &'a Config) -> Option<&'a dyn Codec> {
+    req.headers()
+        .get_all(http::header::SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .find_map(|protocol| codecs(config).find(|codec| codec.subprotocol() == protocol.trim()))
+}
+
+/// encode a message with a codec. It's converted into a [Value] first as that's what codecs work with.
+fn encode(codec: &dyn Codec, msg: &impl Serialize) -> Result<Vec<u8>, CodecError> {
+    codec.encode(&serde_json::to_value(msg).map_err(CodecError::new)?)
+}
+
+/// check if the client asked for a `text/event-stream` response. This is what an `EventSource` sends.
+fn accepts_event_stream(req: &httpz::Request) -> bool {
+    accepts(req, "text/event-stream")
+}
+
+/// check if the client asked for the responses of a batch to be streamed as newline-delimited JSON.
+/// This is only done when the webserver supports streaming responses, otherwise the batch is returned as a JSON array.
+fn accepts_ndjson(req: &httpz::Request) -> bool {
+    accepts(req, "application/x-ndjson") && req.server().supports_streaming()
+}
+
+fn accepts(req: &httpz::Request, mime: &str) -> bool {
+    req.headers()
+        .get_all(http::header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.contains(mime))
+}
+
+/// Subscribe to a subscription using Server-Sent Events. Every message of the subscription is sent as the `data` of an event in the same format as over a WebSocket.
+///
+/// The `id` of each event counts up from the `Last-Event-ID` header sent by a reconnecting `EventSource`, so it's the number of events the client has received.
+/// Events aren't buffered between connections. Instead the header is passed to the subscription as [RequestContext::last_event_id](crate::internal::RequestContext::last_event_id)
+/// so it can resume from where the client left off.
+pub async fn handle_sse<TCtx, TCtxFn, TCtxFnMarker>(
     ctx_fn: TCtxFn,
     req: httpz::Request,
-    router: &Arc<Router<TCtx>>,
+    router: Arc<Router<TCtx>>,
 ) -> impl HttpResponse
 where
     TCtx: Send + Sync + 'static,
     TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>,
 {
-    match serde_json::from_slice::<Vec<jsonrpc::Request>>(req.body()) {
-        Ok(reqs) => {
-            let mut responses = Vec::with_capacity(reqs.len());
-            for op in reqs {
-                // TODO: Make `TCtx` require clone and only run the ctx function once for the whole batch.
-                let ctx = ctx_fn.exec(req._internal_dangerously_clone());
+    if !req.server().supports_streaming() {
+        tracing::debug!("Streaming responses are not supported on your webserver!");
 
-                let ctx = match ctx {
-                    Ok(v) => v,
-                    Err(_err) => {
-                        tracing::error!("Error executing context function: {}", _err);
-
-                        return Ok(
-                            Response::builder()
-                                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                                .header("Content-Type", "application/json")
-                                .body(b"[]".to_vec())?,
-                            // TODO: Props just return `None` here so that we don't allocate or need a clone.
-                        );
-                    }
-                };
+        return error_response(
+            ExecError::Internal("streaming responses are not supported by this server".into()),
+            &JsonCodec,
+            &router.config,
+        );
+    }
 
-                // Catch panics so they don't take out the whole batch
-                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| async {
-                    let mut response = None as Option<jsonrpc::Response>;
-                    handle_json_rpc(ctx, op, Cow::Borrowed(router), &mut response).await;
-                    response
-                }));
-
-                match result {
-                    Ok(fut) => {
-                        if let Some(response) = fut.await {
-                            responses.push(response);
+    // Has to be allocated because `TCtxFn` takes ownership of `req`
+    let procedure_name = req.uri().path()[1..].to_string();
+
+    let input = match req
+        .query_pairs()
+        .and_then(|mut params| params.find(|e| e.0 == "input").map(|e| e.1))
+        .map(|v| serde_json::from_str(&v))
+        .unwrap_or(Ok(None as Option<Value>))
+    {
+        Ok(input) => input,
+        Err(_err) => {
+            tracing::error!(
+                "Error passing parameters to operation 'subscription' with key '{:?}': {}",
+                procedure_name,
+                _err
+            );
+
+            return error_response(
+                ExecError::DeserializingArgErr(_err),
+                &JsonCodec,
+                &router.config,
+            );
+        }
+    };
+
+    let last_event_id = req
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+
+    let ctx = match ctx_fn.exec(req) {
+        Ok(v) => v,
+        Err(_err) => {
+            tracing::error!("Error executing context function: {}", _err);
+
+            return error_response(_err, &JsonCodec, &router.config);
+        }
+    };
+
+    let mut subscriptions = HashMap::new();
+    let (mut tx, rx) = mpsc::channel::<jsonrpc::Response>(router.config.subscription_buffer_size);
+    handle_json_rpc(
+        ctx,
+        jsonrpc::Request {
+            jsonrpc: None,
+            // Subscriptions require an id. There is only ever one on this connection.
+            id: RequestId::Number(0),
+            inner: RequestInner::Subscription {
+                path: procedure_name,
+                input: jsonrpc::NewOrOldInput::Old(input),
+            },
+            last_event_id,
+        },
+        Cow::Borrowed(&router),
+        SubscriptionSender(&mut tx, &mut subscriptions),
+    )
+    .await;
+    // The subscription task holds its own sender so the stream ends once it has completed.
+    drop(tx);
+
+    // A zero interval disables the heartbeat. `interval_at` would panic on it.
+    let heartbeat = router.config.sse_heartbeat_interval;
+    let interval = (!heartbeat.is_zero())
+        .then(|| tokio::time::interval_at(tokio::time::Instant::now() + heartbeat, heartbeat));
+
+    // The subscriptions map is kept alive by the stream. When the client disconnects the stream is dropped which shuts down the subscription.
+    let body = stream::unfold(
+        (rx, interval, subscriptions, last_event_id.unwrap_or(0)),
+        |(mut rx, mut interval, subscriptions, mut id)| async move {
+            let chunk = tokio::select! {
+                biased; // Note: Order is important here
+                msg = rx.next() => {
+                    let msg = msg?;
+                    match serde_json::to_string(&msg) {
+                        Ok(data) => {
+                            id += 1;
+                            format!("id: {}\ndata: {}\n\n", id, data).into_bytes()
+                        }
+                        Err(_err) => {
+                            tracing::error!("Error serializing Server-Sent Events message: {}", _err);
+
+                            Vec::new()
                         }
-                    }
-                    Err(_err) => {
-                        tracing::error!(
-                            "Panic occurred while executing JSON-RPC handler: {:?}",
-                            _err
-                        );
                     }
                 }
-            }
+                _ = async {
+                    match interval.as_mut() {
+                        Some(interval) => { interval.tick().await; }
+                        None => pending().await,
+                    }
+                } => b": heartbeat\n\n".to_vec(),
+            };
+
+            Some((chunk, (rx, interval, subscriptions, id)))
+        },
+    );
+
+    httpz::streaming_response(
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "text/event-stream")
+            .header("Cache-Control", "no-cache")
+            // Stops Nginx from buffering the events.
+            .header("X-Accel-Buffering", "no"),
+        body,
+    )
+}
+
+pub async fn handle_http_batch<TCtx, TCtxFn, TCtxFnMarker>(
+    ctx_fn: TCtxFn,
+    req: httpz::Request,
+    router: &Arc<Router<TCtx>>,
+) -> impl HttpResponse
+where
+    TCtx: Send + Sync + 'static,
+    TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>,
+{
+    let reqs = decode_batch(&req, &router.config);
+    let codec = response_codec(&req, &router.config);
+    let ndjson = accepts_ndjson(&req);
+    match reqs {
+        Ok(reqs) => {
+            exec_batch(reqs, router, codec, ndjson, || {
+                ctx_fn.exec(req._internal_dangerously_clone())
+            })
+            .await
+        }
+        Err(_err) => {
+            tracing::error!("Error deserializing batch request: {}", _err);
+
+            error_response(
+                Error::with_cause(
+                    ErrorCode::BadRequest,
+                    "error deserializing batch request".into(),
+                    _err,
+                ),
+                codec,
+                &router.config,
+            )
+        }
+    }
+}
 
-            match serde_json::to_vec(&responses) {
-                Ok(v) => Ok(Response::builder()
-                    .status(StatusCode::OK)
-                    .header("Content-Type", "application/json")
-                    .body(v)?),
+/// Like [handle_http_batch] but the context function is only run once and the context is cloned for every request in the batch.
+pub async fn handle_http_batch_with_shared_ctx<TCtx, TCtxFn, TCtxFnMarker>(
+    ctx_fn: TCtxFn,
+    req: httpz::Request,
+    router: &Arc<Router<TCtx>>,
+) -> impl HttpResponse
+where
+    TCtx: Clone + Send + Sync + 'static,
+    TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>,
+{
+    let reqs = decode_batch(&req, &router.config);
+    let codec = response_codec(&req, &router.config);
+    let ndjson = accepts_ndjson(&req);
+    match reqs {
+        Ok(reqs) => {
+            let ctx = match ctx_fn.exec(req) {
+                Ok(v) => v,
                 Err(_err) => {
-                    tracing::error!("Error serializing batch request: {}", _err);
+                    tracing::error!("Error executing context function: {}", _err);
 
-                    Ok(Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .header("Content-Type", "application/json")
-                        .body(b"[]".to_vec())?)
+                    return error_response(_err, codec, &router.config);
                 }
-            }
+            };
+
+            exec_batch(reqs, router, codec, ndjson, || Ok(ctx.clone())).await
         }
         Err(_err) => {
             tracing::error!("Error deserializing batch request: {}", _err);
 
-            Ok(Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .header("Content-Type", "application/json")
-                .body(b"[]".to_vec())?)
+            error_response(
+                Error::with_cause(
+                    ErrorCode::BadRequest,
+                    "error deserializing batch request".into(),
+                    _err,
+                ),
+                codec,
+                &router.config,
+            )
+        }
+    }
+}
+
+/// decode the requests of a batch with the codec of the request body.
+fn decode_batch(
+    req: &httpz::Request,
+    config: &Config,
+) -> Result<Vec<jsonrpc::Request>, CodecError> {
+    request_codec(req, config)
+        .decode(req.body())
+        .and_then(|v| serde_json::from_value(v).map_err(CodecError::new))
+}
+
+/// Execute every request in a batch. When `ndjson` is set each response is streamed to the client as a line of JSON as soon as its procedure finishes,
+/// so they arrive in completion order and must be matched to the requests by their `id`. Otherwise an array encoded with `codec` is returned in request order.
+async fn exec_batch<TCtx>(
+    reqs: Vec<jsonrpc::Request>,
+    router: &Arc<Router<TCtx>>,
+    codec: &dyn Codec,
+    ndjson: bool,
+    mut ctx_fn: impl FnMut() -> Result<TCtx, ExecError>,
+) -> Result<Response<Vec<u8>>, httpz::Error>
+where
+    TCtx: Send + Sync + 'static,
+{
+    let mut ops = Vec::with_capacity(reqs.len());
+    for op in reqs {
+        let ctx = match ctx_fn() {
+            Ok(v) => v,
+            Err(_err) => {
+                tracing::error!("Error executing context function: {}", _err);
+
+                return error_response(_err, codec, &router.config);
+            }
+        };
+
+        ops.push((ctx, op));
+    }
+
+    let limit = router
+        .config
+        .batch_concurrency_limit
+        .unwrap_or(ops.len())
+        .max(1);
+
+    let responses = stream::iter(ops).map({
+        let router = router.clone();
+        move |(ctx, op)| exec_batch_request(ctx, op, router.clone())
+    });
+
+    if ndjson {
+        // `buffer_unordered` yields each response as soon as it's ready instead of waiting for the ones before it.
+        let body = responses
+            .buffer_unordered(limit)
+            .filter_map(ready)
+            .filter_map(|resp| {
+                ready(match serde_json::to_vec(&resp) {
+                    Ok(mut v) => {
+                        v.push(b'\n');
+                        Some(v)
+                    }
+                    Err(_err) => {
+                        tracing::error!("Error serializing batch response: {}", _err);
+                        None
+                    }
+                })
+            });
+
+        return httpz::streaming_response(
+            Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/x-ndjson")
+                // Stops Nginx from buffering the responses.
+                .header("X-Accel-Buffering", "no"),
+            body,
+        );
+    }
+
+    // `buffered` runs up to `limit` requests at once while still yielding them in request order.
+    let responses = responses
+        .buffered(limit)
+        .filter_map(ready)
+        .collect::<Vec<_>>()
+        .await;
+
+    match encode(codec, &responses) {
+        Ok(v) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", codec.content_type())
+            .body(v)?),
+        Err(_err) => {
+            tracing::error!("Error serializing batch request: {}", _err);
+
+            error_response(
+                ExecError::Internal("error serializing batch response".into()),
+                codec,
+                &router.config,
+            )
+        }
+    }
+}
+
+async fn exec_batch_request<TCtx>(
+    ctx: TCtx,
+    op: jsonrpc::Request,
+    router: Arc<Router<TCtx>>,
+) -> Option<jsonrpc::Response>
+where
+    TCtx: Send + Sync + 'static,
+{
+    let mut response = None as Option<jsonrpc::Response>;
+
+    // Catch panics so they don't take out the whole batch
+    match AssertUnwindSafe(handle_json_rpc(ctx, op, Cow::Owned(router), &mut response))
+        .catch_unwind()
+        .await
+    {
+        Ok(()) => response,
+        Err(_err) => {
+            tracing::error!(
+                "Panic occurred while executing JSON-RPC handler: {:?}",
+                _err
+            );
+            None
         }
     }
 }
@@ -358,92 +917,232 @@ where
         tracing::debug!("Websocket are not supported on your webserver!");
 
         // TODO: Make this error be picked up on the frontend and expose it with a logical name
-        return Ok(Response::builder()
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .body(vec![])?);
-    }
-
-    WebsocketUpgrade::from_req(req, move |req, mut socket| async move {
-		let mut subscriptions = HashMap::new();
-		let (mut tx, mut rx) = mpsc::channel::<jsonrpc::Response>(100);
-
-		loop {
-			tokio::select! {
-					biased; // Note: Order is important here
-					msg = rx.next() => {
-						match socket.send(Message::Text(match serde_json::to_string(&msg) {
-							Ok(v) => v,
-							Err(_err) => {
-								tracing::error!("Error serializing websocket message: {}", _err);
-
-								continue;
-							}
-						})).await {
-							Ok(_) => {}
-							Err(_err) => {
-								tracing::error!("Error sending websocket message: {}", _err);
-
-								continue;
-							}
-						}
-					}
-					msg = socket.next() => {
-						match msg {
-							Some(Ok(msg) )=> {
-							   let res = match msg {
-									Message::Text(text) => serde_json::from_str::<Value>(&text),
-									Message::Binary(binary) => serde_json::from_slice(&binary),
-									Message::Ping(_) | Message::Pong(_) | Message::Close(_) => {
-										continue;
-									}
-									Message::Frame(_) => unreachable!(),
-								};
-
-								match res.and_then(|v| match v.is_array() {
-										true => serde_json::from_value::<Vec<jsonrpc::Request>>(v),
-										false => serde_json::from_value::<jsonrpc::Request>(v).map(|v| vec![v]),
-									}) {
-									Ok(reqs) => {
-										for request in reqs {
-											let ctx = ctx_fn.exec(req._internal_dangerously_clone());
-											handle_json_rpc(match ctx {
-												Ok(v) => v,
-												Err(_err) => {
-													tracing::error!("Error executing context function: {}", _err);
-
-													continue;
-												}
-											}, request, Cow::Borrowed(&router), SubscriptionSender(&mut tx, &mut subscriptions)
-											).await;
-										}
-									},
-									Err(_err) => {
-										tracing::error!("Error parsing websocket message: {}", _err);
-
-										// TODO: Send report of error to frontend
-
-										continue;
-									}
-								};
-							}
-							Some(Err(_err)) => {
-								tracing::error!("Error in websocket: {}", _err);
-
-								// TODO: Send report of error to frontend
-
-								continue;
-							},
-							None => {
-								tracing::debug!("Shutting down websocket connection");
-
-								// TODO: Send report of error to frontend
-
-								return;
-							},
-						}
-					}
-			}
-		}
-	})
-	.into_response()
+        return error_response(
+            ExecError::Internal("websockets are not supported by this server".into()),
+            response_codec(&req, &router.config),
+            &router.config,
+        );
+    }
+
+    let protocol = websocket_codec(&req, &router.config).map(|codec| codec.subprotocol());
+
+    let mut upgrade = WebsocketUpgrade::from_req(req, move |req, socket| {
+        handle_websocket_connection(socket, router, protocol, move || {
+            ctx_fn.exec(req._internal_dangerously_clone())
+        })
+    });
+    if let Some(protocol) = protocol {
+        upgrade = upgrade.protocol(protocol);
+    }
+    upgrade.into_response()
+}
+
+/// Like [handle_websocket] but the context function is only run once when the connection is established and the context is cloned for every request on it.
+pub fn handle_websocket_with_shared_ctx<TCtx, TCtxFn, TCtxFnMarker>(
+    ctx_fn: TCtxFn,
+    req: httpz::Request,
+    router: Arc<Router<TCtx>>,
+) -> impl HttpResponse
+where
+    TCtx: Clone + Send + Sync + 'static,
+    TCtxFn: TCtxFunc<TCtx, TCtxFnMarker>,
+{
+    tracing::debug!("Accepting websocket connection");
+
+    if !req.server().supports_websockets() {
+        tracing::debug!("Websocket are not supported on your webserver!");
+
+        // TODO: Make this error be picked up on the frontend and expose it with a logical name
+        return error_response(
+            ExecError::Internal("websockets are not supported by this server".into()),
+            response_codec(&req, &router.config),
+            &router.config,
+        );
+    }
+
+    let protocol = websocket_codec(&req, &router.config).map(|codec| codec.subprotocol());
+
+    let mut upgrade = WebsocketUpgrade::from_req(req, move |req, socket| async move {
+        let ctx = match ctx_fn.exec(req) {
+            Ok(v) => v,
+            Err(_err) => {
+                tracing::error!("Error executing context function: {}", _err);
+
+                // TODO: Send report of error to frontend
+
+                return;
+            }
+        };
+
+        handle_websocket_connection(socket, router, protocol, move || Ok(ctx.clone())).await
+    });
+    if let Some(protocol) = protocol {
+        upgrade = upgrade.protocol(protocol);
+    }
+    upgrade.into_response()
+}
+
+/// The messages on the connection are encoded with the codec of the negotiated subprotocol, or JSON if there isn't one.
+async fn handle_websocket_connection<TCtx>(
+    mut socket: Box<dyn Websocket + Send>,
+    router: Arc<Router<TCtx>>,
+    protocol: Option<&'static str>,
+    mut ctx_fn: impl FnMut() -> Result<TCtx, ExecError> + Send,
+) where
+    TCtx: Send + Sync + 'static,
+{
+    let codec = protocol
+        .and_then(|protocol| codecs(&router.config).find(|codec| codec.subprotocol() == protocol))
+        .unwrap_or(&JsonCodec);
+    let mut subscriptions = HashMap::new();
+    let (mut tx, mut rx) =
+        mpsc::channel::<jsonrpc::Response>(router.config.subscription_buffer_size);
+    let semaphore = Arc::new(Semaphore::new(router.config.websocket_concurrency_limit));
+    // The senders for cancelling in-flight queries and mutations.
+    // They are dropped along with this function when the connection closes which cancels everything still running.
+    let mut in_flight = Vec::<(RequestId, oneshot::Sender<()>)>::new();
+
+    loop {
+        tokio::select! {
+            biased; // Note: Order is important here
+            msg = rx.next() => {
+                let msg = match encode(codec, &msg) {
+                    Ok(v) => v,
+                    Err(_err) => {
+                        tracing::error!("Error serializing websocket message: {}", _err);
+
+                        continue;
+                    }
+                };
+                let msg = match codec.is_binary() {
+                    true => Message::Binary(msg),
+                    false => match String::from_utf8(msg) {
+                        Ok(v) => Message::Text(v),
+                        Err(_err) => {
+                            tracing::error!("Error serializing websocket message: {}", _err);
+
+                            continue;
+                        }
+                    },
+                };
+
+                match socket.send(msg).await {
+                    Ok(_) => {}
+                    Err(_err) => {
+                        tracing::error!("Error sending websocket message: {}", _err);
+
+                        continue;
+                    }
+                }
+            }
+            msg = socket.next() => {
+                match msg {
+                    Some(Ok(msg)) => {
+                        let res = match msg {
+                            Message::Text(text) => codec.decode(text.as_bytes()),
+                            Message::Binary(binary) => codec.decode(&binary),
+                            Message::Ping(_) | Message::Pong(_) | Message::Close(_) => {
+                                continue;
+                            }
+                            Message::Frame(_) => unreachable!(),
+                        };
+
+                        match res.and_then(|v| match v.is_array() {
+                            true => serde_json::from_value::<Vec<jsonrpc::Request>>(v),
+                            false => serde_json::from_value::<jsonrpc::Request>(v).map(|v| vec![v]),
+                        }.map_err(CodecError::new)) {
+                            Ok(reqs) => {
+                                for request in reqs {
+                                    if let RequestInner::QueryCancel { input: id } = &request.inner {
+                                        // Dropping the sender resolves the cancel future of the request.
+                                        if !matches!(id, RequestId::Null) {
+                                            in_flight.retain(|(req_id, _)| req_id != id);
+                                        }
+
+                                        continue;
+                                    }
+
+                                    let ctx = match ctx_fn() {
+                                        Ok(v) => v,
+                                        Err(_err) => {
+                                            tracing::error!("Error executing context function: {}", _err);
+
+                                            continue;
+                                        }
+                                    };
+
+                                    match request.inner {
+                                        RequestInner::Query { .. } | RequestInner::Mutation { .. } => {
+                                            // Queries and mutations run as their own task so a slow one doesn't block reading
+                                            // further messages or flushing subscription events. They report back through `tx`.
+                                            // The permit is taken before spawning so a client can't pile up an unbounded number of tasks.
+                                            // Waiting for it stops reading the socket, which pushes back on the client until a running request is done.
+                                            // This can't deadlock on `rx` not being drained as every task sends it's response with it's own sender,
+                                            // which always has room for one message.
+                                            let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                                                // The semaphore is never closed
+                                                continue;
+                                            };
+
+                                            let router = router.clone();
+                                            let sender = OwnedMpscSender::new(tx.clone());
+
+                                            let (cancel_tx, cancel_rx) = oneshot::channel::<()>();
+                                            in_flight.retain(|(_, tx)| !tx.is_closed());
+                                            in_flight.push((request.id.clone(), cancel_tx));
+
+                                            tokio::spawn(async move {
+                                                let _permit = permit;
+
+                                                handle_json_rpc_with_cancel(
+                                                    ctx,
+                                                    request,
+                                                    Cow::Owned(router),
+                                                    sender,
+                                                    async move {
+                                                        cancel_rx.await.ok();
+                                                    },
+                                                )
+                                                .await;
+                                            });
+                                        }
+                                        _ => {
+                                            handle_json_rpc(
+                                                ctx,
+                                                request,
+                                                Cow::Borrowed(&router),
+                                                SubscriptionSender(&mut tx, &mut subscriptions),
+                                            )
+                                            .await;
+                                        }
+                                    }
+                                }
+                            }
+                            Err(_err) => {
+                                tracing::error!("Error parsing websocket message: {}", _err);
+
+                                // TODO: Send report of error to frontend
+
+                                continue;
+                            }
+                        };
+                    }
+                    Some(Err(_err)) => {
+                        tracing::error!("Error in websocket: {}", _err);
+
+                        // TODO: Send report of error to frontend
+
+                        continue;
+                    }
+                    None => {
+                        tracing::debug!("Shutting down websocket connection");
+
+                        // TODO: Send report of error to frontend
+
+                        return;
+                    }
+                }
+            }
+        }
+    }
 }
@@ -17,12 +17,15 @@ use tokio::sync::oneshot;
 
 use crate::{
     internal::jsonrpc::{
-        self, handle_json_rpc, OwnedSender, RequestId, Sender, SubscriptionUpgrade,
+        self, handle_json_rpc, handle_json_rpc_with_cancel, OwnedSender, RequestId, RequestInner,
+        Sender, SubscriptionUpgrade,
     },
     Router,
 };
 
 type SubscriptionMap = Arc<futures_locks::Mutex<HashMap<RequestId, oneshot::Sender<()>>>>;
+// Dropping one of these senders cancels the query or mutation it belongs to.
+type InFlightMap = Arc<Mutex<Vec<(RequestId, oneshot::Sender<()>)>>>;
 
 pub struct TauriSender(Window<Wry>, SubscriptionMap);
 
@@ -70,7 +73,7 @@ where
 {
     router: Arc<Router<TCtx, TMeta>>,
     ctx_fn: TCtxFn,
-    windows: Mutex<HashMap<u64, SubscriptionMap>>,
+    windows: Mutex<HashMap<u64, (SubscriptionMap, InFlightMap)>>,
 }
 
 impl<TCtxFn, TCtx, TMeta> WindowManager<TCtxFn, TCtx, TMeta>
@@ -80,6 +83,15 @@ where
     TCtxFn: Fn(Window<Wry>) -> TCtx + Send + Sync + 'static,
 {
     pub fn new(ctx_fn: TCtxFn, router: Arc<Router<TCtx, TMeta>>) -> Arc<Self> {
+        // Tauri events are always JSON so a codec would silently never be used.
+        #[allow(clippy::panic)]
+        if let Some(codec) = router.config.codecs.first() {
+            panic!(
+                "rspc error: the Tauri integration only supports JSON, however the router was configured with the '{}' codec.",
+                codec.content_type()
+            );
+        }
+
         Arc::new(Self {
             router,
             ctx_fn,
@@ -93,15 +105,21 @@ where
         let window_hash = hasher.finish();
 
         let mut windows = self.windows.lock().expect("Failed to lock windows mutex");
-        // Shutdown all subscriptions for the previously loaded page is there was one
-        if let Some(subscriptions) = windows.get(&window_hash) {
+        // Shutdown all subscriptions and in-flight requests for the previously loaded page is there was one
+        if let Some((subscriptions, in_flight)) = windows.get(&window_hash) {
             let mut subscriptions = block_on(subscriptions.lock());
             for (_, tx) in subscriptions.drain() {
                 tx.send(()).ok();
             }
+
+            in_flight
+                .lock()
+                .expect("Failed to lock in-flight mutex")
+                .clear();
         } else {
             let subscriptions = SubscriptionMap::default();
-            windows.insert(window_hash, subscriptions.clone());
+            let in_flight = InFlightMap::default();
+            windows.insert(window_hash, (subscriptions.clone(), in_flight.clone()));
             drop(windows);
 
             window.listen("plugin:rspc:transport", {
@@ -142,16 +160,50 @@ where
                     };
 
                     for req in reqs {
+                        if let RequestInner::QueryCancel { input: id } = &req.inner {
+                            if !matches!(id, RequestId::Null) {
+                                in_flight
+                                    .lock()
+                                    .expect("Failed to lock in-flight mutex")
+                                    .retain(|(req_id, _)| req_id != id);
+                            }
+
+                            continue;
+                        }
+
                         let ctx = (self.ctx_fn)(window.clone());
                         let router = self.router.clone();
                         let window = window.clone();
 
-                        spawn(handle_json_rpc(
-                            ctx,
-                            req,
-                            Cow::Owned(router),
-                            TauriSender(window, subscriptions.clone()),
-                        ));
+                        match req.inner {
+                            RequestInner::Query { .. } | RequestInner::Mutation { .. } => {
+                                let (cancel_tx, cancel_rx) = oneshot::channel::<()>();
+                                {
+                                    let mut in_flight =
+                                        in_flight.lock().expect("Failed to lock in-flight mutex");
+                                    in_flight.retain(|(_, tx)| !tx.is_closed());
+                                    in_flight.push((req.id.clone(), cancel_tx));
+                                }
+
+                                spawn(handle_json_rpc_with_cancel(
+                                    ctx,
+                                    req,
+                                    Cow::Owned(router),
+                                    TauriSender(window, subscriptions.clone()),
+                                    async move {
+                                        cancel_rx.await.ok();
+                                    },
+                                ));
+                            }
+                            _ => {
+                                spawn(handle_json_rpc(
+                                    ctx,
+                                    req,
+                                    Cow::Owned(router),
+                                    TauriSender(window, subscriptions.clone()),
+                                ));
+                            }
+                        }
                     }
                 }
             });
@@ -163,12 +215,17 @@ where
         window.hash(&mut hasher);
         let window_hash = hasher.finish();
 
-        if let Some(rspc_window) = self
+        if let Some((rspc_window, in_flight)) = self
             .windows
             .lock()
             .expect("Failed to lock windows mutex")
             .remove(&window_hash)
         {
+            in_flight
+                .lock()
+                .expect("Failed to lock in-flight mutex")
+                .clear();
+
             spawn(async move {
                 let mut subscriptions = rspc_window.lock().await;
                 for (_, tx) in subscriptions.drain() {
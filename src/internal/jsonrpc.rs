@@ -33,6 +33,9 @@ pub struct Request {
     pub id: RequestId,
     #[serde(flatten)]
     pub inner: RequestInner,
+    /// The `Last-Event-ID` header of a subscription over Server-Sent Events. Refer to [RequestContext::last_event_id](crate::internal::RequestContext::last_event_id).
+    #[serde(skip)]
+    pub last_event_id: Option<u64>,
 }
 
 /// TODO
@@ -62,6 +65,10 @@ pub enum RequestInner {
     // The new system doesn't take an input but the old one does so this is design to make them compatible
     // TODO: Remove value and `SubscriptionStop` struct in future
     SubscriptionStop(#[serde(default)] Option<SubscriptionStop>),
+    /// Cancel an in-flight query or mutation by the id of the request that started it.
+    QueryCancel {
+        input: RequestId,
+    },
 }
 
 // TODO: Remove this in future
@@ -103,6 +110,8 @@ pub enum ResponseInner {
     Event(Value),
     Response(Value),
     Error(JsonRPCError),
+    /// Sent once a subscription's stream has ended and no more events will be sent for it.
+    Complete,
 }
 
 /// TODO
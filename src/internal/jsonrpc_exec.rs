@@ -1,7 +1,7 @@
 use std::{
     borrow::Cow,
-    collections::HashMap,
-    future::{ready, Future, Ready},
+    collections::{HashMap, VecDeque},
+    future::{pending, ready, Future, Ready},
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
@@ -11,11 +11,11 @@ use futures::StreamExt;
 use serde_json::Value;
 use tokio::sync::oneshot;
 
-use crate::{internal::jsonrpc, ExecError, Router};
+use crate::{internal::jsonrpc, BackpressurePolicy, ExecError, Router};
 
 use super::{
     jsonrpc::{NewOrOldInput, RequestId, RequestInner, ResponseInner},
-    AsyncMap, ProcedureKind, RequestContext,
+    AsyncMap, ProcedureKind,
 };
 
 pub enum UnreachableSender {}
@@ -105,6 +105,22 @@ impl OwnedSender for OwnedMpscSender {
     }
 }
 
+// Unlike [SubscriptionSender] this owns the channel so it can be moved into a spawned task.
+// It doesn't support subscriptions as they need access to the connection's subscription map.
+impl<'a> Sender<'a> for OwnedMpscSender {
+    type SendFut = OwnedMpscSenderOwnedSendFut;
+    type SubscriptionMap = HashMap<RequestId, oneshot::Sender<()>>; // Unused
+    type OwnedSender = UnreachableSender; // Unused
+
+    fn subscription(self) -> SubscriptionUpgrade<'a, Self> {
+        SubscriptionUpgrade::Unsupported(self)
+    }
+
+    fn send(self, resp: jsonrpc::Response) -> Self::SendFut {
+        OwnedMpscSenderOwnedSendFut(self.0, Some(resp))
+    }
+}
+
 pub struct OwnedMpscSenderSendFut<'a>(
     &'a mut futures_channel::mpsc::Sender<jsonrpc::Response>,
     Option<jsonrpc::Response>,
@@ -115,22 +131,43 @@ impl<'a> Future for OwnedMpscSenderSendFut<'a> {
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
-        match this.0.poll_ready(cx) {
-            Poll::Ready(Ok(_)) => {
-                this.0
-                    .try_send(this.1.take().expect("Future polled after completion"))
-                    .map_err(|_err| {
-                        tracing::error!("Failed to send response: {}", _err);
-                    })
-                    .ok();
-                Poll::Ready(())
-            }
-            Poll::Ready(Err(_err)) => {
-                tracing::error!("Failed to reserve capacity to send response: {}", _err);
-                Poll::Ready(())
-            }
-            Poll::Pending => Poll::Pending,
+        poll_mpsc_send(this.0, &mut this.1, cx)
+    }
+}
+
+pub struct OwnedMpscSenderOwnedSendFut(
+    futures_channel::mpsc::Sender<jsonrpc::Response>,
+    Option<jsonrpc::Response>,
+);
+
+impl Future for OwnedMpscSenderOwnedSendFut {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        poll_mpsc_send(&mut this.0, &mut this.1, cx)
+    }
+}
+
+fn poll_mpsc_send(
+    chan: &mut futures_channel::mpsc::Sender<jsonrpc::Response>,
+    resp: &mut Option<jsonrpc::Response>,
+    cx: &mut Context<'_>,
+) -> Poll<()> {
+    match chan.poll_ready(cx) {
+        Poll::Ready(Ok(_)) => {
+            chan.try_send(resp.take().expect("Future polled after completion"))
+                .map_err(|_err| {
+                    tracing::error!("Failed to send response: {}", _err);
+                })
+                .ok();
+            Poll::Ready(())
         }
+        Poll::Ready(Err(_err)) => {
+            tracing::error!("Failed to reserve capacity to send response: {}", _err);
+            Poll::Ready(())
+        }
+        Poll::Pending => Poll::Pending,
     }
 }
 
@@ -142,6 +179,23 @@ pub fn handle_json_rpc<'a, TCtx, TMeta>(
     router: Cow<'a, Arc<Router<TCtx, TMeta>>>,
     sender: impl Sender<'a> + 'a,
 ) -> impl Future<Output = ()> + Send + 'a
+where
+    TCtx: Send + 'static,
+    TMeta: Send + Sync + 'static,
+{
+    handle_json_rpc_with_cancel(ctx, req, router, sender, pending())
+}
+
+/// Like [handle_json_rpc] but queries and mutations are aborted when `cancel` resolves.
+/// A cancelled request responds with [ErrorCode::ClientClosedRequest](crate::ErrorCode::ClientClosedRequest).
+#[allow(clippy::manual_async_fn)]
+pub fn handle_json_rpc_with_cancel<'a, TCtx, TMeta>(
+    ctx: TCtx,
+    req: jsonrpc::Request,
+    router: Cow<'a, Arc<Router<TCtx, TMeta>>>,
+    sender: impl Sender<'a> + 'a,
+    cancel: impl Future<Output = ()> + Send + 'a,
+) -> impl Future<Output = ()> + Send + 'a
 where
     TCtx: Send + 'static,
     TMeta: Send + Sync + 'static,
@@ -152,388 +206,286 @@ where
                 .send(jsonrpc::Response {
                     jsonrpc: "2.0",
                     id: req.id.clone(),
-                    result: ResponseInner::Error(ExecError::InvalidJsonRpcVersion.into()),
+                    result: ResponseInner::Error(
+                        ExecError::InvalidJsonRpcVersion.into_jsonrpc(&router.config),
+                    ),
                 })
                 .await;
             return;
         }
 
-        match req.inner {
-            RequestInner::Query { path, input } => {
-                match router
-                    .queries
-                    .store
-                    .get(&path)
-                    .ok_or_else(|| ExecError::OperationNotFound(path.clone()))
-                {
-                    Ok(op) => {
-                        let mut stream = match op
-                            .exec
-                            .call(
-                                ctx,
-                                input.unwrap_or(Value::Null),
-                                RequestContext {
-                                    kind: ProcedureKind::Query,
-                                    path,
-                                },
-                            )
-                            .await
-                        {
-                            Ok(s) => s,
-                            Err(err) => {
-                                tracing::error!(
-                                    "Error executing query operation {:?}: {:?}",
-                                    req.id,
-                                    err
-                                );
-
-                                sender
-                                    .send(jsonrpc::Response {
-                                        jsonrpc: "2.0",
-                                        id: req.id,
-                                        result: ResponseInner::Error(err.into()),
-                                    })
-                                    .await;
-                                return;
-                            }
+        let (kind, path, input) = match req.inner {
+            RequestInner::Query { path, input } => (ProcedureKind::Query, path, input),
+            RequestInner::Mutation { path, input } => (ProcedureKind::Mutation, path, input),
+            RequestInner::Subscription { path, input } => {
+                match sender.subscription() {
+                    SubscriptionUpgrade::Supported(mut sender, mut subscriptions) => {
+                        let (id, input) = match input {
+                            NewOrOldInput::New(id, input) => (id, input),
+                            NewOrOldInput::Old(input) => (req.id, input),
                         };
 
-                        // // TODO: Middleware could mess with this assumption so think about that.
-                        // match stream.size_hint() {
-                        //     (1, Some(1)) => {}
-                        //     hint => {
-                        //         #[cfg(debug_assertions)]
-                        //         panic!("Internal rspc errror: invalid size hint {:?}", hint);
-                        //         #[cfg(not(debug_assertions))]
-                        //         return;
-                        //     }
-                        // }
-
-                        if let Some(data) = stream.next().await {
-                            match data {
-                                Ok(value) => {
-                                    sender
-                                        .send(jsonrpc::Response {
-                                            jsonrpc: "2.0",
-                                            id: req.id.clone(),
-                                            result: ResponseInner::Response(value),
-                                        })
-                                        .await;
-                                }
-                                Err(error) => {
-                                    tracing::error!(
-                                        "Error executing query operation {:?}: {:?}",
-                                        req.id,
-                                        error
-                                    );
-
-                                    sender
-                                        .send(jsonrpc::Response {
-                                            jsonrpc: "2.0",
-                                            id: req.id.clone(),
-                                            result: ResponseInner::Error(error.into()),
-                                        })
-                                        .await;
-                                }
-                            }
-                        } else {
-                            tracing::warn!(
-                                "Stream ended unexpectedly for query request: {:?}",
-                                req.id
-                            );
-
+                        if matches!(id, RequestId::Null) {
                             sender
                                 .send(jsonrpc::Response {
                                     jsonrpc: "2.0",
-                                    id: req.id.clone(),
+                                    id: id.clone(),
                                     result: ResponseInner::Error(
-                                        ExecError::Internal(
-                                            "Stream ended unexpectedly".to_string(),
-                                        )
-                                        .into(),
+                                        ExecError::ErrSubscriptionWithNullId
+                                            .into_jsonrpc(&router.config),
+                                    ),
+                                })
+                                .await;
+                        } else if subscriptions.contains_key(&id).await {
+                            sender
+                                .send(jsonrpc::Response {
+                                    jsonrpc: "2.0",
+                                    id: id.clone(),
+                                    result: ResponseInner::Error(
+                                        ExecError::ErrSubscriptionDuplicateId
+                                            .into_jsonrpc(&router.config),
                                     ),
                                 })
                                 .await;
                         }
-                    }
-                    Err(err) => {
-                        tracing::error!("Error executing query operation {:?}: {:?}", req.id, err);
 
-                        sender
-                            .send(jsonrpc::Response {
-                                jsonrpc: "2.0",
-                                id: req.id,
-                                result: ResponseInner::Error(err.into()),
-                            })
-                            .await;
-                    }
-                }
-            }
-            RequestInner::Mutation { path, input } => {
-                match router
-                    .mutations
-                    .store
-                    .get(&path)
-                    .ok_or_else(|| ExecError::OperationNotFound(path.clone()))
-                {
-                    Ok(op) => {
-                        let mut stream = match op
-                            .exec
-                            .call(
-                                ctx,
-                                input.unwrap_or(Value::Null),
-                                RequestContext {
-                                    kind: ProcedureKind::Mutation,
-                                    path,
-                                },
-                            )
-                            .await
+                        if let Err(err) = router
+                            .subscriptions
+                            .store
+                            .get(&path)
+                            .ok_or_else(|| ExecError::OperationNotFound(path.clone()))
                         {
-                            Ok(s) => s,
-                            Err(err) => {
-                                tracing::error!(
-                                    "Error executing mutation operation {:?}: {:?}",
-                                    req.id,
-                                    err
-                                );
-
-                                sender
-                                    .send(jsonrpc::Response {
-                                        jsonrpc: "2.0",
-                                        id: req.id,
-                                        result: ResponseInner::Error(err.into()),
-                                    })
-                                    .await;
-                                return;
-                            }
-                        };
+                            tracing::error!(
+                                "Error executing subscription operation {:?}: {:?}",
+                                id,
+                                err
+                            );
 
-                        // // TODO: Middleware could mess with this assumption so think about that.
-                        // match stream.size_hint() {
-                        //     (1, Some(1)) => {}
-                        //     hint => {
-                        //         #[cfg(debug_assertions)]
-                        //         panic!("Internal rspc errror: invalid size hint {:?}", hint);
-                        //         #[cfg(not(debug_assertions))]
-                        //         return;
-                        //     }
-                        // }
-
-                        if let Some(data) = stream.next().await {
-                            match data {
-                                Ok(v) => {
-                                    sender
-                                        .send(jsonrpc::Response {
-                                            jsonrpc: "2.0",
-                                            id: req.id,
-                                            result: ResponseInner::Response(v),
-                                        })
-                                        .await;
-                                }
+                            sender
+                                .send(jsonrpc::Response {
+                                    jsonrpc: "2.0",
+                                    id,
+                                    result: ResponseInner::Error(err.into_jsonrpc(&router.config)),
+                                })
+                                .await;
+                            return;
+                        }
+
+                        let router = to_owned(router);
+                        let last_event_id = req.last_event_id;
+                        let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
+                        subscriptions.insert(id.clone(), shutdown_tx).await;
+                        tokio::spawn(async move {
+                            let mut input = input.unwrap_or(Value::Null);
+                            router.bigints_from_strings(
+                                &ProcedureKind::Subscription,
+                                &path,
+                                &mut input,
+                            );
+
+                            let mut stream = match router
+                                .exec_stream(
+                                    ProcedureKind::Subscription,
+                                    ctx,
+                                    path.clone(),
+                                    input,
+                                    last_event_id,
+                                )
+                                .await
+                            {
+                                Ok(s) => s,
                                 Err(err) => {
                                     tracing::error!(
-                                        "Error executing mutation operation {:?}: {:?}",
-                                        req.id,
+                                        "Error executing subscription operation {:?}: {:?}",
+                                        id,
                                         err
                                     );
 
                                     sender
                                         .send(jsonrpc::Response {
                                             jsonrpc: "2.0",
-                                            id: req.id,
-                                            result: ResponseInner::Error(err.into()),
+                                            id,
+                                            result: ResponseInner::Error(
+                                                router.procedure_error_to_jsonrpc(
+                                                    &ProcedureKind::Subscription,
+                                                    &path,
+                                                    err,
+                                                ),
+                                            ),
                                         })
                                         .await;
+                                    return;
                                 }
-                            }
-                        } else {
-                            tracing::warn!(
-                                "Stream ended unexpectedly for mutation operation: {:?}",
-                                req.id
-                            );
+                            };
+
+                            let buffer_size = router.config.subscription_buffer_size;
+                            let policy = router.config.backpressure_policy;
+
+                            // Responses waiting to be sent to the client. This lets the backpressure policy decide what
+                            // happens when the stream produces events faster than the client can receive them.
+                            let mut queue = VecDeque::with_capacity(buffer_size);
+                            let mut done = false;
+
+                            loop {
+                                tokio::select! {
+                                    biased; // Note: Order matters
+                                    _ = &mut shutdown_rx => {
+                                        tracing::debug!("Removing subscription with id '{:?}'", id);
+                                        break;
+                                    }
+                                    // `select!` builds every branch's future before checking preconditions, so the queue can't be assumed non-empty here.
+                                    // The front is cloned so it isn't lost if this future is dropped by another branch completing first.
+                                    _ = async {
+                                        match queue.front().cloned() {
+                                            Some(resp) => sender.send(resp).await,
+                                            None => pending().await,
+                                        }
+                                    }, if !queue.is_empty() => {
+                                        queue.pop_front();
 
-                            sender
-                                .send(jsonrpc::Response {
-                                    jsonrpc: "2.0",
-                                    id: req.id.clone(),
-                                    result: ResponseInner::Error(
-                                        ExecError::Internal(
-                                            "Stream ended unexpectedly".to_string(),
-                                        )
-                                        .into(),
-                                    ),
-                                })
-                                .await;
-                        }
-                    }
-                    Err(err) => {
-                        tracing::error!(
-                            "Error executing mutation operation {:?}: {:?}",
-                            req.id,
-                            err
-                        );
+                                        if done && queue.is_empty() {
+                                            break;
+                                        }
+                                    }
+                                    v = stream.next(), if !done && (policy != BackpressurePolicy::Block || queue.len() < buffer_size) => {
+                                        let result = match v {
+                                            Some(Ok(mut v)) => {
+                                                router.bigints_to_strings(&ProcedureKind::Subscription, &path, &mut v);
+                                                ResponseInner::Event(v)
+                                            }
+                                            Some(Err(err)) => {
+                                                tracing::error!("Subscription {:?} error: {:?}", id, err);
+                                                ResponseInner::Error(router.procedure_error_to_jsonrpc(&ProcedureKind::Subscription, &path, err))
+                                            }
+                                            None => {
+                                                done = true;
+                                                ResponseInner::Complete
+                                            }
+                                        };
+
+                                        if queue.len() >= buffer_size && !done {
+                                            match policy {
+                                                BackpressurePolicy::Block => unreachable!(),
+                                                BackpressurePolicy::DropOldest => {
+                                                    queue.pop_front();
+                                                }
+                                                BackpressurePolicy::LatestOnly => {}
+                                                BackpressurePolicy::CloseWithError => {
+                                                    tracing::warn!("Closing subscription {:?} as its buffer is full", id);
+
+                                                    done = true;
+                                                    queue.push_back(jsonrpc::Response {
+                                                        jsonrpc: "2.0",
+                                                        id: id.clone(),
+                                                        result: ResponseInner::Error(ExecError::ErrSubscriptionBufferFull.into_jsonrpc(&router.config)),
+                                                    });
+                                                    continue;
+                                                }
+                                            }
+                                        }
+
+                                        // Errors and the completion message are never replaced so the client always finds out about them.
+                                        if policy == BackpressurePolicy::LatestOnly && matches!(result, ResponseInner::Event(_)) {
+                                            queue.retain(|resp: &jsonrpc::Response| !matches!(resp.result, ResponseInner::Event(_)));
+                                        }
 
+                                        queue.push_back(jsonrpc::Response {
+                                            jsonrpc: "2.0",
+                                            id: id.clone(),
+                                            result,
+                                        });
+                                    }
+                                }
+                            }
+                        });
+                    }
+                    SubscriptionUpgrade::Unsupported(sender) => {
                         sender
                             .send(jsonrpc::Response {
                                 jsonrpc: "2.0",
-                                id: req.id,
-                                result: ResponseInner::Error(err.into()),
+                                id: req.id.clone(),
+                                result: ResponseInner::Error(
+                                    ExecError::UnsupportedMethod("Subscription".to_string())
+                                        .into_jsonrpc(&router.config),
+                                ),
                             })
                             .await;
                     }
                 }
+                return;
             }
-            RequestInner::Subscription { path, input } => match sender.subscription() {
-                SubscriptionUpgrade::Supported(mut sender, mut subscriptions) => {
-                    let (id, input) = match input {
-                        NewOrOldInput::New(id, input) => (id, input),
-                        NewOrOldInput::Old(input) => (req.id, input),
-                    };
-
-                    if matches!(id, RequestId::Null) {
-                        sender
-                            .send(jsonrpc::Response {
-                                jsonrpc: "2.0",
-                                id: id.clone(),
-                                result: ResponseInner::Error(
-                                    ExecError::ErrSubscriptionWithNullId.into(),
-                                ),
-                            })
+            RequestInner::SubscriptionStop(input) => {
+                match sender.subscription() {
+                    SubscriptionUpgrade::Supported(_sender, mut subscriptions) => {
+                        subscriptions
+                            // We `unwrap_or` for backwards compatibility with the tRPC style client I had. // TODO: Remove this in the future
+                            .remove(&input.map(|i| i.input).unwrap_or(req.id))
                             .await;
-                    } else if subscriptions.contains_key(&id).await {
+                    }
+                    SubscriptionUpgrade::Unsupported(sender) => {
                         sender
                             .send(jsonrpc::Response {
                                 jsonrpc: "2.0",
-                                id: id.clone(),
+                                id: req.id.clone(),
                                 result: ResponseInner::Error(
-                                    ExecError::ErrSubscriptionDuplicateId.into(),
+                                    ExecError::UnsupportedMethod("Subscription".to_string())
+                                        .into_jsonrpc(&router.config),
                                 ),
                             })
                             .await;
                     }
+                }
+                return;
+            }
+            // Cancelling is handled by the transport as it's the one tracking the in-flight requests.
+            RequestInner::QueryCancel { .. } => {
+                sender
+                    .send(jsonrpc::Response {
+                        jsonrpc: "2.0",
+                        id: req.id.clone(),
+                        result: ResponseInner::Error(
+                            ExecError::UnsupportedMethod("QueryCancel".to_string())
+                                .into_jsonrpc(&router.config),
+                        ),
+                    })
+                    .await;
+                return;
+            }
+        };
 
-                    if let Err(err) = router
-                        .subscriptions
-                        .store
-                        .get(&path)
-                        .ok_or_else(|| ExecError::OperationNotFound(path.clone()))
-                    {
-                        tracing::error!(
-                            "Error executing subscription operation {:?}: {:?}",
-                            id,
-                            err
-                        );
-
-                        sender
-                            .send(jsonrpc::Response {
-                                jsonrpc: "2.0",
-                                id,
-                                result: ResponseInner::Error(err.into()),
-                            })
-                            .await;
-                        return;
-                    }
-
-                    let router = to_owned(router);
-                    let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
-                    subscriptions.insert(id.clone(), shutdown_tx).await;
-                    tokio::spawn(async move {
-                        let op = router.subscriptions.store.get(&path).expect(
-                            "Fatal rspc error: Rust's borrowing rules have been broken. An `&T` was modified.",
-                        );
-
-                        let mut stream = match op
-                            .exec
-                            .call(
-                                ctx,
-                                input.unwrap_or(Value::Null),
-                                RequestContext {
-                                    kind: ProcedureKind::Query,
-                                    path,
-                                },
-                            )
-                            .await
-                        {
-                            Ok(s) => s,
-                            Err(err) => {
-                                tracing::error!(
-                                    "Error executing subscription operation {:?}: {:?}",
-                                    id,
-                                    err
-                                );
+        let mut input = input.unwrap_or(Value::Null);
+        router.bigints_from_strings(&kind, &path, &mut input);
 
-                                sender
-                                    .send(jsonrpc::Response {
-                                        jsonrpc: "2.0",
-                                        id,
-                                        result: ResponseInner::Error(err.into()),
-                                    })
-                                    .await;
-                                return;
-                            }
-                        };
+        let result = tokio::select! {
+            biased; // Note: Order matters
+            _ = cancel => Err(ExecError::ErrRequestCancelled),
+            result = router.exec_raw(kind.clone(), ctx, path.clone(), input) => result,
+        };
 
-                        loop {
-                            tokio::select! {
-                                biased; // Note: Order matters
-                                _ = &mut shutdown_rx => {
-                                    tracing::debug!("Removing subscription with id '{:?}'", id);
-                                    break;
-                                }
-                                v = stream.next() => {
-                                    match v {
-                                        Some(Ok(v)) => {
-                                            sender.send(jsonrpc::Response {
-                                                jsonrpc: "2.0",
-                                                id: id.clone(),
-                                                result: ResponseInner::Event(v),
-                                            })
-                                            .await;
-                                        }
-                                        Some(Err(_err)) => {
-                                            tracing::error!("Subscription {:?} error: {:?}", id, _err);
-                                        }
-                                        None => {
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    });
-                }
-                SubscriptionUpgrade::Unsupported(sender) => {
-                    sender
-                        .send(jsonrpc::Response {
-                            jsonrpc: "2.0",
-                            id: req.id.clone(),
-                            result: ResponseInner::Error(
-                                ExecError::UnsupportedMethod("Subscription".to_string()).into(),
-                            ),
-                        })
-                        .await;
-                }
-            },
-            RequestInner::SubscriptionStop(input) => match sender.subscription() {
-                SubscriptionUpgrade::Supported(_sender, mut subscriptions) => {
-                    subscriptions
-                        // We `unwrap_or` for backwards compatibility with the tRPC style client I had. // TODO: Remove this in the future
-                        .remove(&input.map(|i| i.input).unwrap_or(req.id))
-                        .await;
-                }
-                SubscriptionUpgrade::Unsupported(sender) => {
-                    sender
-                        .send(jsonrpc::Response {
-                            jsonrpc: "2.0",
-                            id: req.id.clone(),
-                            result: ResponseInner::Error(
-                                ExecError::UnsupportedMethod("Subscription".to_string()).into(),
-                            ),
-                        })
-                        .await;
-                }
-            },
-        }
+        let result = match result {
+            Ok(mut value) => {
+                router.bigints_to_strings(&kind, &path, &mut value);
+                ResponseInner::Response(value)
+            }
+            Err(err) => {
+                tracing::error!(
+                    "Error executing {} operation {:?}: {:?}",
+                    kind.to_str(),
+                    req.id,
+                    err
+                );
+
+                ResponseInner::Error(router.procedure_error_to_jsonrpc(&kind, &path, err))
+            }
+        };
+
+        sender
+            .send(jsonrpc::Response {
+                jsonrpc: "2.0",
+                id: req.id,
+                result,
+            })
+            .await;
     }
 }
 
@@ -544,3 +496,205 @@ fn to_owned<T>(arc: Cow<'_, Arc<T>>) -> Arc<T> {
         Cow::Owned(arc) => arc,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_channel::mpsc;
+
+    use crate::{Config, ErrorCode};
+
+    use super::*;
+
+    /// Runs a subscription which produces 10 events with a buffer of 2 without draining it until the stream has ended.
+    async fn run_subscription(policy: BackpressurePolicy) -> Vec<ResponseInner> {
+        let router = Router::<()>::new()
+            .config(
+                Config::new()
+                    .subscription_buffer_size(2)
+                    .backpressure_policy(policy),
+            )
+            .subscription("numbers", |t| t(|_, _: ()| futures::stream::iter(0..10u32)))
+            .build()
+            .arced();
+
+        let (mut tx, rx) = mpsc::channel(0);
+        let mut subscriptions = HashMap::new();
+        handle_json_rpc(
+            (),
+            jsonrpc::Request {
+                jsonrpc: None,
+                id: RequestId::Number(1),
+                inner: RequestInner::Subscription {
+                    path: "numbers".into(),
+                    input: NewOrOldInput::Old(None),
+                },
+                last_event_id: None,
+            },
+            Cow::Borrowed(&router),
+            SubscriptionSender(&mut tx, &mut subscriptions),
+        )
+        .await;
+        drop(tx);
+
+        // Give the subscription time to run its stream to completion while nothing is being received.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let resps = tokio::time::timeout(Duration::from_secs(5), rx.collect::<Vec<_>>())
+            .await
+            .expect("subscription didn't finish");
+        drop(subscriptions);
+
+        resps.into_iter().map(|resp| resp.result).collect()
+    }
+
+    fn events(resps: &[ResponseInner]) -> Vec<u64> {
+        resps
+            .iter()
+            .filter_map(|resp| match resp {
+                ResponseInner::Event(v) => v.as_u64(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn backpressure_block() {
+        let resps = run_subscription(BackpressurePolicy::Block).await;
+
+        assert_eq!(events(&resps), (0..10).collect::<Vec<_>>());
+        assert!(matches!(resps.last(), Some(ResponseInner::Complete)));
+    }
+
+    #[tokio::test]
+    async fn backpressure_drop_oldest() {
+        let resps = run_subscription(BackpressurePolicy::DropOldest).await;
+        let events = events(&resps);
+
+        assert!(matches!(resps.first(), Some(ResponseInner::Event(_))));
+        assert!(events.len() < 10, "no events were dropped: {events:?}");
+        assert!(events.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(events[events.len() - 2..], [8, 9]);
+        assert!(matches!(resps.last(), Some(ResponseInner::Complete)));
+    }
+
+    #[tokio::test]
+    async fn backpressure_latest_only() {
+        let resps = run_subscription(BackpressurePolicy::LatestOnly).await;
+        let events = events(&resps);
+
+        assert!(matches!(resps.first(), Some(ResponseInner::Event(_))));
+        assert!(events.len() < 10, "no events were dropped: {events:?}");
+        assert_eq!(events.last(), Some(&9));
+        assert!(matches!(resps.last(), Some(ResponseInner::Complete)));
+    }
+
+    #[tokio::test]
+    async fn backpressure_close_with_error() {
+        let resps = run_subscription(BackpressurePolicy::CloseWithError).await;
+        let events = events(&resps);
+
+        assert!(matches!(resps.first(), Some(ResponseInner::Event(_))));
+        assert!(events.len() < 10, "no events were dropped: {events:?}");
+        assert!(
+            matches!(resps.last(), Some(ResponseInner::Error(err)) if err.code == i32::from(ErrorCode::InternalServerError.to_status_code()))
+        );
+        assert!(!resps
+            .iter()
+            .any(|resp| matches!(resp, ResponseInner::Complete)));
+    }
+
+    // This is how `handle_sse` passes on the `Last-Event-ID` of a reconnecting `EventSource`.
+    #[tokio::test]
+    async fn subscription_resumes_from_last_event_id() {
+        let router = Router::<()>::new()
+            .middleware(|mw| {
+                mw.middleware(|mw| async move {
+                    let received = mw.req.last_event_id().unwrap_or(0);
+                    Ok(mw.with_ctx(received))
+                })
+            })
+            .subscription("numbers", |t| {
+                t(|received: u64, _: ()| futures::stream::iter(received..5))
+            })
+            .build()
+            .arced();
+
+        let (mut tx, rx) = mpsc::channel(0);
+        let mut subscriptions = HashMap::new();
+        handle_json_rpc(
+            (),
+            jsonrpc::Request {
+                jsonrpc: None,
+                id: RequestId::Number(1),
+                inner: RequestInner::Subscription {
+                    path: "numbers".into(),
+                    input: NewOrOldInput::Old(None),
+                },
+                last_event_id: Some(3),
+            },
+            Cow::Borrowed(&router),
+            SubscriptionSender(&mut tx, &mut subscriptions),
+        )
+        .await;
+        drop(tx);
+
+        let resps = tokio::time::timeout(Duration::from_secs(5), rx.collect::<Vec<_>>())
+            .await
+            .expect("subscription didn't finish");
+        drop(subscriptions);
+
+        let resps = resps
+            .into_iter()
+            .map(|resp| resp.result)
+            .collect::<Vec<_>>();
+        assert_eq!(events(&resps), [3, 4]);
+    }
+
+    // This is the message `wsLink` and `tauriLink` send when a query or mutation is aborted.
+    #[test]
+    fn query_cancel_from_client() {
+        let req: jsonrpc::Request = serde_json::from_value(serde_json::json!({
+            "method": "queryCancel",
+            "params": { "input": "abc" }
+        }))
+        .expect("failed to parse request");
+
+        assert!(
+            matches!(req.inner, RequestInner::QueryCancel { input: RequestId::String(id) } if id == "abc")
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelled_query_responds_with_client_closed_request() {
+        let router = Router::<()>::new()
+            .query("slow", |t| {
+                t(|_, _: ()| async { pending::<Result<(), crate::Error>>().await })
+            })
+            .build()
+            .arced();
+
+        let mut resp = None;
+        handle_json_rpc_with_cancel(
+            (),
+            jsonrpc::Request {
+                jsonrpc: None,
+                id: RequestId::Number(1),
+                inner: RequestInner::Query {
+                    path: "slow".into(),
+                    input: None,
+                },
+                last_event_id: None,
+            },
+            Cow::Borrowed(&router),
+            &mut resp,
+            ready(()),
+        )
+        .await;
+
+        assert!(
+            matches!(resp.map(|resp| resp.result), Some(ResponseInner::Error(err)) if err.code == i32::from(ErrorCode::ClientClosedRequest.to_status_code()))
+        );
+    }
+}
@@ -5,6 +5,11 @@ use serde_json::Value;
 
 use crate::{ExecError, MiddlewareLike};
 
+// Re-exported so `rspc::internal::ProcedureKind` keeps working.
+pub use crate::ProcedureKind;
+
+use super::ProcedureMeta;
+
 pub trait MiddlewareBuilderLike<TCtx> {
     type LayerContext: 'static;
 
@@ -183,30 +188,28 @@ where
     }
 }
 
-// TODO: Is this a duplicate of any type?
-// TODO: Move into public API cause it might be used in middleware
-#[derive(Debug, Clone)]
-pub enum ProcedureKind {
-    Query,
-    Mutation,
-    Subscription,
-}
-
-impl ProcedureKind {
-    pub fn to_str(&self) -> &'static str {
-        match self {
-            ProcedureKind::Query => "query",
-            ProcedureKind::Mutation => "mutation",
-            ProcedureKind::Subscription => "subscription",
-        }
-    }
-}
-
 // TODO: Maybe rename to `Request` or something else. Also move into Public API cause it might be used in middleware
 #[derive(Debug, Clone)]
 pub struct RequestContext {
     pub kind: ProcedureKind,
     pub path: String, // TODO: String slice??
+    pub(crate) meta: Option<ProcedureMeta>,
+    pub(crate) last_event_id: Option<u64>,
+}
+
+impl RequestContext {
+    /// The metadata the procedure was registered with.
+    /// Returns `None` if the procedure has no metadata or it isn't of type `TMeta`.
+    pub fn meta<TMeta: 'static>(&self) -> Option<&TMeta> {
+        self.meta.as_deref()?.downcast_ref()
+    }
+
+    /// The `Last-Event-ID` an `EventSource` sent when it reconnected to a subscription over Server-Sent Events.
+    /// Event ids count the events sent to the client, so a subscription can use this to skip what the client has already received.
+    /// `None` for every other request.
+    pub fn last_event_id(&self) -> Option<u64> {
+        self.last_event_id
+    }
 }
 
 // #[deprecated = "Going to be removed in v1.0.0. The new middleware system removes the need for this."]
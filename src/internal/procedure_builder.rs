@@ -1,33 +1,89 @@
-use std::{marker::PhantomData, ops::Deref};
+use std::{borrow::Cow, marker::PhantomData, ops::Deref, sync::Arc, time::Duration};
 
-pub struct UnbuiltProcedureBuilder<TLayerCtx, TResolver> {
-    deref_handler: fn(TResolver) -> BuiltProcedureBuilder<TResolver>,
+use super::{ProcedureDocs, ProcedureMeta};
+
+pub struct UnbuiltProcedureBuilder<TLayerCtx, TResolver, TMeta = ()> {
+    deref_handler: fn(TResolver) -> BuiltProcedureBuilder<TResolver, TMeta>,
     phantom: PhantomData<TLayerCtx>,
 }
 
-impl<TLayerCtx, TResolver> Default for UnbuiltProcedureBuilder<TLayerCtx, TResolver> {
+impl<TLayerCtx, TResolver, TMeta> Default for UnbuiltProcedureBuilder<TLayerCtx, TResolver, TMeta> {
     fn default() -> Self {
         Self {
-            deref_handler: |resolver| BuiltProcedureBuilder { resolver },
+            deref_handler: |resolver| BuiltProcedureBuilder {
+                resolver,
+                timeout: None,
+                meta: None,
+                docs: ProcedureDocs::default(),
+                phantom: PhantomData,
+            },
             phantom: PhantomData,
         }
     }
 }
 
-impl<TLayerCtx, TResolver> UnbuiltProcedureBuilder<TLayerCtx, TResolver> {
-    pub fn resolver(self, resolver: TResolver) -> BuiltProcedureBuilder<TResolver> {
+impl<TLayerCtx, TResolver, TMeta> UnbuiltProcedureBuilder<TLayerCtx, TResolver, TMeta> {
+    pub fn resolver(self, resolver: TResolver) -> BuiltProcedureBuilder<TResolver, TMeta> {
         (self.deref_handler)(resolver)
     }
 }
 
-impl<TLayerCtx, TResolver> Deref for UnbuiltProcedureBuilder<TLayerCtx, TResolver> {
-    type Target = fn(resolver: TResolver) -> BuiltProcedureBuilder<TResolver>;
+impl<TLayerCtx, TResolver, TMeta> Deref for UnbuiltProcedureBuilder<TLayerCtx, TResolver, TMeta> {
+    type Target = fn(resolver: TResolver) -> BuiltProcedureBuilder<TResolver, TMeta>;
 
     fn deref(&self) -> &Self::Target {
         &self.deref_handler
     }
 }
 
-pub struct BuiltProcedureBuilder<TResolver> {
+pub struct BuiltProcedureBuilder<TResolver, TMeta = ()> {
     pub resolver: TResolver,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) meta: Option<ProcedureMeta>,
+    pub(crate) docs: ProcedureDocs,
+    pub(crate) phantom: PhantomData<TMeta>,
+}
+
+impl<TResolver, TMeta> BuiltProcedureBuilder<TResolver, TMeta> {
+    /// Abort the procedure with a `Timeout` error if it doesn't complete within the given duration.
+    /// For subscriptions this only applies to setting up the stream.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Attach metadata to the procedure. It can be read by middleware using [RequestContext::meta](super::RequestContext::meta).
+    pub fn meta(mut self, meta: TMeta) -> Self
+    where
+        TMeta: Send + Sync + 'static,
+    {
+        self.meta = Some(Arc::new(meta));
+        self
+    }
+
+    /// Describe the procedure. The description is exported as JSDoc in the Typescript bindings.
+    /// Use [docs!](crate::docs) to describe it with doc comments.
+    pub fn description(mut self, description: impl Into<Cow<'static, str>>) -> Self {
+        self.docs.description = Some(description.into());
+        self
+    }
+
+    /// Mark the procedure as deprecated. It's exported with a JSDoc `@deprecated` tag and calling it logs a warning.
+    pub fn deprecated(mut self, reason: impl Into<Cow<'static, str>>) -> Self {
+        self.docs.deprecated = Some(reason.into());
+        self
+    }
+}
+
+/// Join the lines of a doc comment captured by [docs!](crate::docs).
+#[doc(hidden)]
+pub fn doc_comment(lines: &[&str]) -> String {
+    lines
+        .iter()
+        // `/// text` is captured as `" text"`
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
 }
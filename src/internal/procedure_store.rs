@@ -1,4 +1,7 @@
-use std::{borrow::Cow, collections::BTreeMap, future::ready, pin::Pin};
+use std::{
+    any::Any, borrow::Cow, collections::BTreeMap, future::ready, panic::Location, pin::Pin,
+    sync::Arc, time::Duration,
+};
 
 use futures::{stream::once, Stream};
 use serde_json::Value;
@@ -18,6 +21,8 @@ pub struct ProcedureDataType {
     pub input: DataType,
     #[specta(type = serde_json::Value)]
     pub result: DataType,
+    #[specta(type = serde_json::Value)]
+    pub error: DataType,
 }
 
 // TODO: Remove this type once v1
@@ -46,12 +51,61 @@ impl<TCtx: Send + 'static> EitherLayer<TCtx> {
     }
 }
 
+/// The metadata attached to a procedure. It's type erased so the procedure store doesn't need to know about the router's `TMeta`.
+pub type ProcedureMeta = Arc<dyn Any + Send + Sync>;
+
+/// The description and deprecation notice of a procedure. These are exported as JSDoc in the Typescript bindings.
+#[derive(Debug, Clone, Default)]
+pub struct ProcedureDocs {
+    pub description: Option<Cow<'static, str>>,
+    /// The reason the procedure is deprecated. Calling a deprecated procedure logs a warning.
+    pub deprecated: Option<Cow<'static, str>>,
+}
+
+/// Everything a procedure was registered with besides it's resolver and types.
+#[derive(Debug, Clone)]
+pub struct ProcedureOptions {
+    /// The timeout of the procedure. If `None` the default from the router's [Config](crate::Config) is used.
+    pub timeout: Option<Duration>,
+    /// The metadata the procedure was registered with.
+    pub meta: Option<ProcedureMeta>,
+    /// Where in the source code the procedure was registered.
+    pub location: &'static Location<'static>,
+    /// The description and deprecation notice of the procedure.
+    pub docs: ProcedureDocs,
+    /// The prefixes of the routers the procedure was merged into joined by `.`, outermost first. Empty if it wasn't merged.
+    pub prefix: String,
+}
+
+impl ProcedureOptions {
+    /// the options of the procedure once it's router is merged into another one with the given prefix and timeout.
+    pub(crate) fn merged(self, prefix: &str, timeout: Option<Duration>) -> Self {
+        Self {
+            timeout: self.timeout.or(timeout),
+            prefix: join_prefixes(prefix, &self.prefix),
+            ..self
+        }
+    }
+}
+
+/// Join the prefix of a router to the prefix of a router merged into it. The prefixes may end with a `.`.
+pub(crate) fn join_prefixes(outer: &str, inner: &str) -> String {
+    [outer, inner]
+        .into_iter()
+        .map(|prefix| prefix.trim_end_matches('.'))
+        .filter(|prefix| !prefix.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
 // TODO: Make private
 pub struct Procedure<TCtx> {
     // TODO: make private -> without breaking Spacedrive
     pub exec: EitherLayer<TCtx>,
     // TODO: make private -> without breaking Spacedrive
     pub ty: ProcedureDataType,
+    /// The timeout, metadata, location and docs of the procedure.
+    pub options: ProcedureOptions,
 }
 
 // TODO: make private
@@ -62,6 +116,15 @@ pub struct ProcedureStore<TCtx> {
 }
 
 impl<TCtx> ProcedureStore<TCtx> {
+    /// Set the timeout of every procedure in the store which doesn't already have one.
+    pub(crate) fn default_timeout(&mut self, timeout: Option<Duration>) {
+        if let Some(timeout) = timeout {
+            for procedure in self.store.values_mut() {
+                procedure.options.timeout.get_or_insert(timeout);
+            }
+        }
+    }
+
     pub const fn new(name: &'static str) -> Self {
         Self {
             name,
@@ -69,7 +132,13 @@ impl<TCtx> ProcedureStore<TCtx> {
         }
     }
 
-    pub fn append(&mut self, key: String, exec: Box<dyn Layer<TCtx>>, ty: ProcedureDataType) {
+    pub fn append(
+        &mut self,
+        key: String,
+        exec: Box<dyn Layer<TCtx>>,
+        ty: ProcedureDataType,
+        options: ProcedureOptions,
+    ) {
         #[allow(clippy::panic)]
         if key.is_empty() || key == "ws" || key.starts_with("rpc.") || key.starts_with("rspc.") {
             panic!(
@@ -92,6 +161,7 @@ impl<TCtx> ProcedureStore<TCtx> {
             Procedure {
                 exec: EitherLayer::Legacy(exec),
                 ty,
+                options,
             },
         );
     }
@@ -102,6 +172,7 @@ impl<TCtx> ProcedureStore<TCtx> {
         key: String,
         exec: L,
         ty: ProcedureDataType,
+        options: ProcedureOptions,
     ) where
         // TODO: move this bound to impl once `alpha` stuff is stable
         TCtx: 'static,
@@ -128,6 +199,7 @@ impl<TCtx> ProcedureStore<TCtx> {
             Procedure {
                 exec: EitherLayer::Alpha(exec.erase()),
                 ty,
+                options,
             },
         );
     }
@@ -0,0 +1,136 @@
+use std::panic::Location;
+
+use specta::DataType;
+
+use crate::{
+    internal::{Procedure, ProcedureDocs, ProcedureMeta},
+    Router,
+};
+
+/// The type of a procedure.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProcedureKind {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl ProcedureKind {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            ProcedureKind::Query => "query",
+            ProcedureKind::Mutation => "mutation",
+            ProcedureKind::Subscription => "subscription",
+        }
+    }
+}
+
+/// Information about a procedure registered on a [Router]. Returned by [Router::procedures].
+#[derive(Debug, Clone)]
+pub struct ProcedureInfo<'a> {
+    kind: ProcedureKind,
+    key: &'a str,
+    prefix: &'a str,
+    input: &'a DataType,
+    result: &'a DataType,
+    error: &'a DataType,
+    meta: Option<&'a ProcedureMeta>,
+    location: &'static Location<'static>,
+    docs: &'a ProcedureDocs,
+}
+
+impl<'a> ProcedureInfo<'a> {
+    pub(crate) fn new<TCtx>(
+        kind: ProcedureKind,
+        key: &'a str,
+        procedure: &'a Procedure<TCtx>,
+    ) -> Self {
+        Self {
+            kind,
+            key,
+            prefix: &procedure.options.prefix,
+            input: &procedure.ty.input,
+            result: &procedure.ty.result,
+            error: &procedure.ty.error,
+            meta: procedure.options.meta.as_ref(),
+            location: procedure.options.location,
+            docs: &procedure.options.docs,
+        }
+    }
+
+    /// whether the procedure is a query, mutation or subscription.
+    pub fn kind(&self) -> &ProcedureKind {
+        &self.kind
+    }
+
+    /// the full key of the procedure including the prefixes of any routers it was merged into.
+    pub fn key(&self) -> &'a str {
+        self.key
+    }
+
+    /// the prefixes of the routers the procedure was merged into joined by `.`, outermost first. Empty if it wasn't merged.
+    pub fn prefix(&self) -> &'a str {
+        self.prefix
+    }
+
+    /// the type of the procedure's input. References to named types can be resolved using [Router::typ_store].
+    pub fn input(&self) -> &'a DataType {
+        self.input
+    }
+
+    /// the type of the procedure's result. For subscriptions this is the type of each event.
+    pub fn result(&self) -> &'a DataType {
+        self.result
+    }
+
+    /// the type of the procedure's typed error. This is an empty tuple if the procedure returns a plain [Error](crate::Error).
+    pub fn error(&self) -> &'a DataType {
+        self.error
+    }
+
+    /// the metadata the procedure was registered with.
+    /// Returns `None` if the procedure has no metadata or it isn't of type `TMeta`.
+    pub fn meta<TMeta: 'static>(&self) -> Option<&'a TMeta> {
+        self.meta?.downcast_ref()
+    }
+
+    /// where in the source code the procedure was registered.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// the description of the procedure if one was set.
+    pub fn description(&self) -> Option<&'a str> {
+        self.docs.description.as_deref()
+    }
+
+    /// the reason the procedure is deprecated. Returns `None` if it isn't deprecated.
+    pub fn deprecated(&self) -> Option<&'a str> {
+        self.docs.deprecated.as_deref()
+    }
+
+    pub(crate) fn docs(&self) -> &'a ProcedureDocs {
+        self.docs
+    }
+}
+
+impl<TCtx, TMeta> Router<TCtx, TMeta>
+where
+    TCtx: Send + 'static,
+{
+    /// Iterate over every procedure on the router. Queries are returned first, then mutations and then subscriptions, each sorted by key.
+    pub fn procedures(&self) -> impl Iterator<Item = ProcedureInfo<'_>> {
+        [
+            (ProcedureKind::Query, &self.queries),
+            (ProcedureKind::Mutation, &self.mutations),
+            (ProcedureKind::Subscription, &self.subscriptions),
+        ]
+        .into_iter()
+        .flat_map(|(kind, store)| {
+            store
+                .store
+                .iter()
+                .map(move |(key, procedure)| ProcedureInfo::new(kind.clone(), key, procedure))
+        })
+    }
+}
@@ -0,0 +1,336 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Map, Value};
+use specta::{
+    datatype::{EnumRepr, Fields, LiteralType, PrimitiveType},
+    internal::detect_duplicate_type_names,
+    DataType, TypeMap,
+};
+use specta_typescript::BigIntExportBehavior;
+
+use crate::{internal::ProcedureKind, ExportError, Router};
+
+impl<TCtx, TMeta> Router<TCtx, TMeta>
+where
+    TCtx: Send + 'static,
+{
+    /// export a JSON Schema (draft 2020-12) document describing the router.
+    ///
+    /// Every named type is put into `$defs` and the input, result and error of each procedure are found at `procedures.<kind>.<key>`.
+    pub fn export_json_schema<TPath: AsRef<Path>>(
+        &self,
+        export_path: TPath,
+    ) -> Result<(), ExportError> {
+        let export_path = PathBuf::from(export_path.as_ref());
+        if let Some(export_dir) = export_path.parent() {
+            fs::create_dir_all(export_dir)?;
+        }
+
+        let schema = self.json_schema()?;
+        serde_json::to_writer_pretty(File::create(export_path)?, &schema)?;
+
+        Ok(())
+    }
+
+    /// build the JSON Schema document which is written by [Router::export_json_schema].
+    pub fn json_schema(&self) -> Result<Value, ExportError> {
+        let defs = named_schemas(&self.typ_store, DEFS, &self.config.ts_bigint)?;
+
+        let schema = |dt: &DataType| schema(dt, &self.typ_store, DEFS, &self.config.ts_bigint);
+        let mut procedures = Map::from_iter(
+            ["queries", "mutations", "subscriptions"]
+                .map(|kind| (kind.to_string(), Value::Object(Map::new()))),
+        );
+        for procedure in self.procedures() {
+            let kind = match procedure.kind() {
+                ProcedureKind::Query => "queries",
+                ProcedureKind::Mutation => "mutations",
+                ProcedureKind::Subscription => "subscriptions",
+            };
+
+            let mut def = Map::new();
+            def.insert("input".into(), schema(procedure.input()));
+            def.insert("result".into(), schema(procedure.result()));
+            // Procedures returning `rspc::Error` don't have a typed error.
+            if !is_unit(procedure.error()) {
+                def.insert("error".into(), schema(procedure.error()));
+            }
+
+            if let Some(Value::Object(map)) = procedures.get_mut(kind) {
+                map.insert(procedure.key().to_string(), Value::Object(def));
+            }
+        }
+
+        Ok(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$defs": defs,
+            "procedures": procedures,
+        }))
+    }
+}
+
+const DEFS: &str = "#/$defs/";
+
+/// The schemas of every named type in the type map, keyed by the type's name.
+pub(crate) fn named_schemas(
+    type_map: &TypeMap,
+    defs: &str,
+    bigint: &BigIntExportBehavior,
+) -> Result<Map<String, Value>, ExportError> {
+    if let Some((name, a, b)) = detect_duplicate_type_names(type_map).into_iter().next() {
+        return Err(ExportError::TsExportErr(
+            specta_typescript::ExportError::DuplicateTypeName(name, a, b),
+        ));
+    }
+
+    Ok(type_map
+        .iter()
+        .map(|(_sid, dt)| {
+            (
+                dt.name().to_string(),
+                schema(&dt.inner, type_map, defs, bigint),
+            )
+        })
+        .collect())
+}
+
+pub(crate) fn is_unit(dt: &DataType) -> bool {
+    matches!(dt, DataType::Tuple(def) if def.elements().is_empty())
+}
+
+/// `bigint` must be the [Config::ts_bigint](crate::Config::ts_bigint) of the router as it changes how 64 and 128 bit integers are sent.
+pub(crate) fn schema(
+    dt: &DataType,
+    type_map: &TypeMap,
+    defs: &str,
+    bigint: &BigIntExportBehavior,
+) -> Value {
+    match dt {
+        DataType::Primitive(ty) => match ty {
+            // These are sent as strings so they match the `string` in the Typescript bindings.
+            PrimitiveType::i64 | PrimitiveType::i128 | PrimitiveType::isize
+                if matches!(bigint, BigIntExportBehavior::String) =>
+            {
+                json!({ "type": "string", "pattern": "^-?\\d+$" })
+            }
+            PrimitiveType::u64 | PrimitiveType::u128 | PrimitiveType::usize
+                if matches!(bigint, BigIntExportBehavior::String) =>
+            {
+                json!({ "type": "string", "pattern": "^\\d+$" })
+            }
+            PrimitiveType::i8
+            | PrimitiveType::i16
+            | PrimitiveType::i32
+            | PrimitiveType::i64
+            | PrimitiveType::i128
+            | PrimitiveType::isize => json!({ "type": "integer" }),
+            PrimitiveType::u8
+            | PrimitiveType::u16
+            | PrimitiveType::u32
+            | PrimitiveType::u64
+            | PrimitiveType::u128
+            | PrimitiveType::usize => json!({ "type": "integer", "minimum": 0 }),
+            PrimitiveType::bool => json!({ "type": "boolean" }),
+            PrimitiveType::char | PrimitiveType::String => json!({ "type": "string" }),
+            _ => json!({ "type": "number" }),
+        },
+        DataType::Literal(literal) => match literal {
+            LiteralType::bool(v) => json!({ "const": v }),
+            LiteralType::char(v) => json!({ "const": v }),
+            LiteralType::String(v) => json!({ "const": v }),
+            LiteralType::None => json!({ "type": "null" }),
+            // TODO: Numeric literals
+            _ => json!({}),
+        },
+        DataType::List(list) => {
+            let mut schema =
+                json!({ "type": "array", "items": schema(list.ty(), type_map, defs, bigint) });
+            if let Some(length) = list.length() {
+                schema["minItems"] = length.into();
+                schema["maxItems"] = length.into();
+            }
+            if list.unique() {
+                schema["uniqueItems"] = true.into();
+            }
+            schema
+        }
+        DataType::Map(map) => json!({
+            "type": "object",
+            "additionalProperties": schema(map.value_ty(), type_map, defs, bigint),
+        }),
+        DataType::Nullable(inner) => json!({
+            "anyOf": [schema(inner, type_map, defs, bigint), { "type": "null" }],
+        }),
+        DataType::Struct(s) => fields_schema(s.fields(), type_map, defs, bigint),
+        DataType::Enum(e) => {
+            let variants = e
+                .variants()
+                .iter()
+                .filter(|(_, variant)| !variant.skip())
+                .map(|(name, variant)| {
+                    let fields = variant.fields();
+                    match e.repr() {
+                        EnumRepr::Untagged => fields_schema(fields, type_map, defs, bigint),
+                        EnumRepr::External => match fields {
+                            Fields::Unit => json!({ "const": name }),
+                            _ => json!({
+                                "type": "object",
+                                "properties": { name.to_string(): fields_schema(fields, type_map, defs, bigint) },
+                                "required": [name],
+                                "additionalProperties": false,
+                            }),
+                        },
+                        EnumRepr::Internal { tag } => {
+                            let tag = json!({
+                                "type": "object",
+                                "properties": { tag.to_string(): { "const": name } },
+                                "required": [tag],
+                            });
+                            match fields {
+                                Fields::Unit => tag,
+                                _ => json!({ "allOf": [tag, fields_schema(fields, type_map, defs, bigint)] }),
+                            }
+                        }
+                        EnumRepr::Adjacent { tag, content } => {
+                            let mut properties = Map::new();
+                            properties.insert(tag.to_string(), json!({ "const": name }));
+                            let mut required = vec![Value::from(tag.to_string())];
+                            if !matches!(fields, Fields::Unit) {
+                                properties
+                                    .insert(content.to_string(), fields_schema(fields, type_map, defs, bigint));
+                                required.push(content.to_string().into());
+                            }
+
+                            json!({ "type": "object", "properties": properties, "required": required })
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            json!({ "anyOf": variants })
+        }
+        DataType::Tuple(tuple) => match tuple.elements().len() {
+            0 => json!({ "type": "null" }),
+            len => json!({
+                "type": "array",
+                "prefixItems": tuple.elements().iter().map(|dt| schema(dt, type_map, defs, bigint)).collect::<Vec<_>>(),
+                "minItems": len,
+                "maxItems": len,
+            }),
+        },
+        // TODO: Generic arguments are not applied so generic types are as loose as their definition.
+        DataType::Reference(reference) => match type_map.get(reference.sid()) {
+            Some(dt) => json!({ "$ref": format!("{}{}", defs, dt.name()) }),
+            None => json!({}),
+        },
+        // `Any`, `Unknown` and generic parameters can be anything.
+        _ => json!({}),
+    }
+}
+
+fn fields_schema(
+    fields: &Fields,
+    type_map: &TypeMap,
+    defs: &str,
+    bigint: &BigIntExportBehavior,
+) -> Value {
+    match fields {
+        Fields::Unit => json!({ "type": "null" }),
+        Fields::Unnamed(fields) => {
+            let items = fields
+                .fields()
+                .iter()
+                .filter_map(|field| field.ty())
+                .map(|dt| schema(dt, type_map, defs, bigint))
+                .collect::<Vec<_>>();
+
+            // A newtype is serialized as it's inner value.
+            match <[Value; 1]>::try_from(items) {
+                Ok([item]) => item,
+                Err(items) => {
+                    let len = items.len();
+                    json!({
+                        "type": "array",
+                        "prefixItems": items,
+                        "minItems": len,
+                        "maxItems": len,
+                    })
+                }
+            }
+        }
+        Fields::Named(fields) => {
+            let mut properties = Map::new();
+            let mut required = Vec::new();
+            let mut flattened = Vec::new();
+
+            if let Some(tag) = fields.tag() {
+                required.push(Value::from(tag.to_string()));
+                properties.insert(tag.to_string(), json!({ "type": "string" }));
+            }
+
+            for (name, field) in fields.fields() {
+                // Fields which are `#[serde(skip)]` don't have a type.
+                let Some(dt) = field.ty() else {
+                    continue;
+                };
+
+                if field.flatten() {
+                    flattened.push(schema(dt, type_map, defs, bigint));
+                    continue;
+                }
+
+                properties.insert(name.to_string(), schema(dt, type_map, defs, bigint));
+                if !field.optional() {
+                    required.push(name.to_string().into());
+                }
+            }
+
+            let schema =
+                json!({ "type": "object", "properties": properties, "required": required });
+            match flattened.is_empty() {
+                true => schema,
+                false => {
+                    flattened.insert(0, schema);
+                    json!({ "allOf": flattened })
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use specta_typescript::BigIntExportBehavior;
+
+    use crate::{Config, Router};
+
+    #[test]
+    fn bigints_sent_as_strings_are_strings() {
+        let router = Router::<()>::new()
+            .config(Config::new().ts_bigint(BigIntExportBehavior::String))
+            .query("big", |t| t(|_, n: i64| Ok(n as u64)))
+            .build();
+
+        let schema = router.json_schema().expect("failed to build schema");
+        let big = &schema["procedures"]["queries"]["big"];
+        assert_eq!(
+            big["input"],
+            json!({ "type": "string", "pattern": "^-?\\d+$" })
+        );
+        assert_eq!(
+            big["result"],
+            json!({ "type": "string", "pattern": "^\\d+$" })
+        );
+
+        let openapi = router.openapi().expect("failed to build OpenAPI document");
+        assert_eq!(
+            openapi["paths"]["/big"]["get"]["parameters"][0]["content"]["application/json"]
+                ["schema"],
+            big["input"]
+        );
+    }
+}
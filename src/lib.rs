@@ -20,16 +20,29 @@
 pub mod alpha;
 // #[deprecated = "Being removed in `v1.0.0`. This will be in the root of the crate."] // TODO
 pub(crate) mod alpha_stable;
+mod bigint;
+mod codec;
 mod config;
+mod cors;
+mod docs;
 mod error;
+mod introspection;
+mod json_schema;
 mod middleware;
+mod openapi;
 mod resolver_result;
 mod router;
 mod router_builder;
 mod selection;
+mod ts_client;
+mod ts_split;
+mod zod;
 
+pub use codec::*;
 pub use config::*;
+pub use cors::*;
 pub use error::*;
+pub use introspection::*;
 pub use middleware::*;
 pub use resolver_result::*;
 pub use router::*;
@@ -0,0 +1,279 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Map, Value};
+use specta::DataType;
+
+use crate::{
+    internal::ProcedureKind,
+    json_schema::{is_unit, named_schemas, schema},
+    ExportError, Router,
+};
+
+const COMPONENTS: &str = "#/components/schemas/";
+/// The schemas added by rspc are prefixed so they can't overwrite a user's type with the same name.
+const ERROR_SCHEMA: &str = "rspc.JsonRPCError";
+
+impl<TCtx, TMeta> Router<TCtx, TMeta>
+where
+    TCtx: Send + 'static,
+{
+    /// export an OpenAPI 3.1 document describing the HTTP routes served by `Router::endpoint`.
+    ///
+    /// Queries are `GET /<key>?input=<json>` and mutations are `POST /<key>` with the input as the JSON body.
+    /// Subscriptions are `GET /<key>?input=<json>` with `Accept: text/event-stream` and respond with Server-Sent Events.
+    /// The schema of each event's `data` is under `x-event-schema` as OpenAPI 3.1 can't describe the events of a stream.
+    /// A subscription which shares its key with a query is left out as a path can only have a single `GET` operation.
+    /// 64 and 128 bit integers are described as strings when [Config::ts_bigint](crate::Config::ts_bigint) sends them as strings.
+    pub fn export_openapi<TPath: AsRef<Path>>(
+        &self,
+        export_path: TPath,
+    ) -> Result<(), ExportError> {
+        let export_path = PathBuf::from(export_path.as_ref());
+        if let Some(export_dir) = export_path.parent() {
+            fs::create_dir_all(export_dir)?;
+        }
+
+        let document = self.openapi()?;
+        serde_json::to_writer_pretty(File::create(export_path)?, &document)?;
+
+        Ok(())
+    }
+
+    /// build the OpenAPI document which is written by [Router::export_openapi].
+    /// The `info` and `servers` fields can be changed on the returned value before it is written.
+    pub fn openapi(&self) -> Result<Value, ExportError> {
+        let mut schemas = named_schemas(&self.typ_store, COMPONENTS, &self.config.ts_bigint)?;
+        schemas.insert(
+            ERROR_SCHEMA.into(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "code": { "type": "integer" },
+                    "message": { "type": "string" },
+                    "data": {},
+                },
+                "required": ["code", "message"],
+            }),
+        );
+
+        let schema =
+            |dt: &DataType| schema(dt, &self.typ_store, COMPONENTS, &self.config.ts_bigint);
+        let mut paths = Map::new();
+        for procedure in self.procedures() {
+            let (method, kind) = match procedure.kind() {
+                ProcedureKind::Query => ("get", "query"),
+                ProcedureKind::Mutation => ("post", "mutation"),
+                ProcedureKind::Subscription => ("get", "subscription"),
+            };
+            let path = format!("/{}", procedure.key());
+            if paths
+                .get(&path)
+                .is_some_and(|path| path.get(method).is_some())
+            {
+                continue;
+            }
+
+            let input = schema(procedure.input());
+            let input_required = !is_unit(procedure.input());
+
+            let error = (!is_unit(procedure.error())).then(|| schema(procedure.error()));
+            let envelope = response_envelope(schema(procedure.result()), error.clone());
+            let ok = match procedure.kind() {
+                ProcedureKind::Subscription => json!({
+                    "description": "The Server-Sent Events of the subscription. The `data` of each event is a JSON-RPC message.",
+                    "content": {
+                        "text/event-stream": {
+                            "schema": { "type": "string" },
+                            "x-event-schema": event_envelope(schema(procedure.result()), error),
+                        },
+                    },
+                }),
+                _ => json!({
+                    "description": "The JSON-RPC response.",
+                    "content": { "application/json": { "schema": envelope } },
+                }),
+            };
+            let mut operation = json!({
+                "operationId": format!("{}.{}", kind, procedure.key()),
+                "tags": [kind],
+                "responses": {
+                    "200": ok,
+                    "default": {
+                        "description": "The JSON-RPC error. The status code matches the `code` of the error.",
+                        "content": { "application/json": { "schema": envelope } },
+                    },
+                },
+            });
+
+            if let Some(description) = procedure.description() {
+                operation["description"] = description.into();
+            }
+            if let Some(reason) = procedure.deprecated() {
+                operation["deprecated"] = true.into();
+                operation["x-deprecation-reason"] = reason.into();
+            }
+
+            match procedure.kind() {
+                ProcedureKind::Query => {
+                    operation["parameters"] = json!([{
+                        "name": "input",
+                        "in": "query",
+                        "required": input_required,
+                        "description": "The JSON encoded input of the query.",
+                        "content": { "application/json": { "schema": input } },
+                    }]);
+                }
+                ProcedureKind::Mutation => {
+                    operation["requestBody"] = json!({
+                        "required": input_required,
+                        "content": { "application/json": { "schema": input } },
+                    });
+                }
+                ProcedureKind::Subscription => {
+                    operation["parameters"] = json!([
+                        {
+                            "name": "input",
+                            "in": "query",
+                            "required": input_required,
+                            "description": "The JSON encoded input of the subscription.",
+                            "content": { "application/json": { "schema": input } },
+                        },
+                        {
+                            "name": "Last-Event-ID",
+                            "in": "header",
+                            "required": false,
+                            "description": "Sent by a reconnecting `EventSource`. The subscription can read it to resume from where the client left off.",
+                            "schema": { "type": "string" },
+                        },
+                    ]);
+                }
+            }
+
+            let path = paths.entry(path).or_insert_with(|| json!({}));
+            path[method] = operation;
+        }
+
+        paths.insert(
+            "/_batch".into(),
+            json!({
+                "post": {
+                    "operationId": "batch",
+                    "description": "Execute multiple queries and mutations in a single request. The responses are returned in request order.",
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "object",
+                                        "properties": {
+                                            "jsonrpc": { "const": "2.0" },
+                                            "id": { "type": ["string", "number", "null"] },
+                                            "method": { "enum": ["query", "mutation"] },
+                                            "params": {
+                                                "type": "object",
+                                                "properties": {
+                                                    "path": { "type": "string" },
+                                                    "input": {},
+                                                },
+                                                "required": ["path"],
+                                            },
+                                        },
+                                        // The id defaults to `null` when it's left out.
+                                        "required": ["method", "params"],
+                                    },
+                                },
+                            },
+                        },
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "The JSON-RPC responses.",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "type": "array", "items": response_envelope(json!({}), None) },
+                                },
+                            },
+                        },
+                        "default": {
+                            "description": "The JSON-RPC error when the whole batch failed. The status code matches the `code` of the error.",
+                            "content": {
+                                "application/json": {
+                                    "schema": response_envelope(json!({}), None),
+                                },
+                            },
+                        },
+                    },
+                },
+            }),
+        );
+
+        Ok(json!({
+            "openapi": "3.1.0",
+            "info": {
+                "title": "rspc",
+                "version": "0.0.0",
+            },
+            "paths": paths,
+            "components": {
+                "schemas": schemas,
+            },
+        }))
+    }
+}
+
+fn response_envelope(result: Value, error: Option<Value>) -> Value {
+    envelope("response", result, error)
+}
+
+/// the JSON-RPC messages sent as the events of a subscription. The stream ends with a `complete` message.
+fn event_envelope(event: Value, error: Option<Value>) -> Value {
+    envelope("event", event, error)
+}
+
+fn envelope(typ: &str, result: Value, error: Option<Value>) -> Value {
+    let error = match error {
+        Some(data) => json!({
+            "allOf": [
+                { "$ref": format!("{}{}", COMPONENTS, ERROR_SCHEMA) },
+                { "type": "object", "properties": { "data": data } },
+            ],
+        }),
+        None => json!({ "$ref": format!("{}{}", COMPONENTS, ERROR_SCHEMA) }),
+    };
+
+    let mut variants = vec![
+        json!({
+            "type": "object",
+            "properties": { "type": { "const": typ }, "data": result },
+            "required": ["type"],
+        }),
+        json!({
+            "type": "object",
+            "properties": { "type": { "const": "error" }, "data": error },
+            "required": ["type", "data"],
+        }),
+    ];
+    if typ == "event" {
+        variants.push(json!({
+            "type": "object",
+            "properties": { "type": { "const": "complete" } },
+            "required": ["type"],
+        }));
+    }
+
+    json!({
+        "type": "object",
+        "properties": {
+            "jsonrpc": { "const": "2.0" },
+            "id": { "type": ["string", "number", "null"] },
+            "result": { "anyOf": variants },
+        },
+        // The id is `null` for errors which aren't tied to a request, such as a body which couldn't be decoded.
+        "required": ["jsonrpc", "result"],
+    })
+}
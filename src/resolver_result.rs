@@ -6,13 +6,14 @@ use specta::Type;
 
 use crate::{
     internal::{LayerResult, ValueOrStream},
-    Error, ExecError,
+    Error, ExecError, ResolverError, Typed,
 };
 
 // For queries and mutations
 
 pub trait RequestLayer<TMarker> {
     type Result: Type;
+    type Error: ResolverError;
 
     fn into_layer_result(self) -> Result<LayerResult, ExecError>;
 }
@@ -23,6 +24,7 @@ where
     T: Serialize + Type,
 {
     type Result = T;
+    type Error = Error;
 
     fn into_layer_result(self) -> Result<LayerResult, ExecError> {
         Ok(LayerResult::Ready(Ok(serde_json::to_value(
@@ -32,6 +34,22 @@ where
     }
 }
 
+pub enum TypedResultMarker {}
+impl<T, TErr> RequestLayer<TypedResultMarker> for Typed<Result<T, TErr>>
+where
+    T: Serialize + Type,
+    TErr: ResolverError,
+{
+    type Result = T;
+    type Error = TErr;
+
+    fn into_layer_result(self) -> Result<LayerResult, ExecError> {
+        RequestLayer::<ResultMarker>::into_layer_result(
+            self.0.map_err(ResolverError::into_resolver_error),
+        )
+    }
+}
+
 pub enum FutureResultMarker {}
 impl<TFut, T> RequestLayer<FutureResultMarker> for TFut
 where
@@ -39,6 +57,32 @@ where
     T: Serialize + Type + Send + 'static,
 {
     type Result = T;
+    type Error = Error;
+
+    fn into_layer_result(self) -> Result<LayerResult, ExecError> {
+        Ok(LayerResult::Future(Box::pin(async move {
+            match self
+                .await
+                .into_layer_result()?
+                .into_value_or_stream()
+                .await?
+            {
+                ValueOrStream::Stream(_) => unreachable!(),
+                ValueOrStream::Value(v) => Ok(v),
+            }
+        })))
+    }
+}
+
+pub enum FutureTypedResultMarker {}
+impl<TFut, T, TErr> RequestLayer<FutureTypedResultMarker> for TFut
+where
+    TFut: Future<Output = Typed<Result<T, TErr>>> + Send + 'static,
+    T: Serialize + Type + Send + 'static,
+    TErr: ResolverError,
+{
+    type Result = T;
+    type Error = TErr;
 
     fn into_layer_result(self) -> Result<LayerResult, ExecError> {
         Ok(LayerResult::Future(Box::pin(async move {
@@ -59,6 +103,7 @@ where
 
 pub trait StreamRequestLayer<TMarker> {
     type Result: Type;
+    type Error: ResolverError;
 
     fn into_layer_result(self) -> Result<LayerResult, ExecError>;
 }
@@ -70,6 +115,7 @@ where
     T: Serialize + Type,
 {
     type Result = T;
+    type Error = Error;
 
     fn into_layer_result(self) -> Result<LayerResult, ExecError> {
         Ok(LayerResult::Stream(Box::pin(self.map(|v| {
@@ -85,6 +131,7 @@ where
     T: Serialize + Type,
 {
     type Result = T;
+    type Error = Error;
 
     fn into_layer_result(self) -> Result<LayerResult, ExecError> {
         Ok(LayerResult::Stream(Box::pin(
@@ -94,6 +141,23 @@ where
     }
 }
 
+pub enum TypedResultStreamMarker {}
+impl<TStream, T, TErr> StreamRequestLayer<TypedResultStreamMarker> for Typed<Result<TStream, TErr>>
+where
+    TStream: Stream<Item = T> + Send + Sync + 'static,
+    T: Serialize + Type,
+    TErr: ResolverError,
+{
+    type Result = T;
+    type Error = TErr;
+
+    fn into_layer_result(self) -> Result<LayerResult, ExecError> {
+        StreamRequestLayer::<ResultStreamMarker>::into_layer_result(
+            self.0.map_err(ResolverError::into_resolver_error),
+        )
+    }
+}
+
 pub enum FutureStreamMarker {}
 impl<TFut, TStream, T> StreamRequestLayer<FutureStreamMarker> for TFut
 where
@@ -102,6 +166,7 @@ where
     T: Serialize + Type,
 {
     type Result = T;
+    type Error = Error;
 
     fn into_layer_result(self) -> Result<LayerResult, ExecError> {
         Ok(LayerResult::FutureValueOrStream(Box::pin(async move {
@@ -120,6 +185,7 @@ where
     T: Serialize + Type,
 {
     type Result = T;
+    type Error = Error;
 
     fn into_layer_result(self) -> Result<LayerResult, ExecError> {
         Ok(LayerResult::FutureValueOrStream(Box::pin(async move {
@@ -131,3 +197,67 @@ where
         })))
     }
 }
+
+pub enum FutureTypedResultStreamMarker {}
+impl<TFut, TStream, T, TErr> StreamRequestLayer<FutureTypedResultStreamMarker> for TFut
+where
+    TFut: Future<Output = Typed<Result<TStream, TErr>>> + Send + 'static,
+    TStream: Stream<Item = T> + Send + Sync + 'static,
+    T: Serialize + Type,
+    TErr: ResolverError,
+{
+    type Result = T;
+    type Error = TErr;
+
+    fn into_layer_result(self) -> Result<LayerResult, ExecError> {
+        Ok(LayerResult::FutureValueOrStream(Box::pin(async move {
+            Ok(ValueOrStream::Stream(Box::pin(
+                self.await
+                    .0
+                    .map_err(|err| ExecError::ErrResolverError(err.into_resolver_error()))?
+                    .map(|v| serde_json::to_value(v).map_err(ExecError::SerializingResultErr)),
+            )))
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use specta::Type;
+
+    use crate::{Error, ErrorCode, Router, Typed};
+
+    #[derive(Serialize, Type)]
+    struct NotFound;
+
+    impl From<NotFound> for Error {
+        fn from(_: NotFound) -> Self {
+            Error::new(ErrorCode::NotFound, "not found".into())
+        }
+    }
+
+    fn find() -> Result<i32, NotFound> {
+        Ok(42)
+    }
+
+    // This only has to compile. Resolvers which never name their error type must keep inferring `rspc::Error`.
+    #[test]
+    fn resolver_error_type_is_inferred() {
+        Router::<()>::new()
+            .query("ok", |t| t(|_, _: ()| Ok(42)))
+            .query("okAsync", |t| t(|_, _: ()| async { Ok(42) }))
+            .query("questionMark", |t| t(|_, _: ()| Ok(find()?)))
+            .query("typed", |t| t(|_, _: ()| Typed(find())))
+            .query("typedAsync", |t| t(|_, _: ()| async { Typed(find()) }))
+            .mutation("ok", |t| t(|_, _: ()| Ok(42)))
+            .subscription("ok", |t| t(|_, _: ()| Ok(futures::stream::iter(0..10))))
+            .subscription("okAsync", |t| {
+                t(|_, _: ()| async { Ok(futures::stream::iter(0..10)) })
+            })
+            .subscription("typed", |t| {
+                t(|_, _: ()| Typed(find().map(|_| futures::stream::iter(0..10))))
+            })
+            .build();
+    }
+}
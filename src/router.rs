@@ -1,23 +1,33 @@
 use std::{
     collections::BTreeMap,
     fs::{self, File},
+    future::Future,
     io::Write,
     marker::PhantomData,
     path::{Path, PathBuf},
+    pin::Pin,
     sync::Arc,
+    time::Duration,
 };
 
+use futures::{Stream, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 use specta::{
     datatype::FunctionResultVariant, internal::detect_duplicate_type_names, DataType, TypeMap,
 };
-use specta_typescript::{datatype, export_named_datatype, BigIntExportBehavior, Typescript};
-// use specta_zod::{BigIntExportBehavior, ExportConfig, export_named_datatype, datatype};
+use specta_typescript::{datatype, export_named_datatype, Typescript};
 
 use crate::{
-    internal::{Procedure, ProcedureStore},
-    Config, ExportError,
+    internal::{Procedure, ProcedureDocs, ProcedureKind, ProcedureStore, RequestContext},
+    ts_split::orphaned_files,
+    Config, Error, ExecError, ExportError, TsDeclarationStyle,
 };
 
+/// Included in the header of every generated file so files which are no longer generated can be found and removed.
+pub(crate) const GENERATED_MARKER: &str =
+    "This file was generated by [rspc](https://github.com/spacedriveapp/rspc)";
+
 /// TODO
 pub struct Router<TCtx = (), TMeta = ()>
 where
@@ -55,37 +65,336 @@ where
         &self.queries.store
     }
 
+    /// List the metadata of every procedure which was registered with metadata of type `T`.
+    /// For routers built with [RouterBuilder](crate::RouterBuilder) `T` is the router's `TMeta`.
+    pub fn procedure_meta<T: 'static>(&self) -> impl Iterator<Item = (ProcedureKind, &str, &T)> {
+        self.procedures().filter_map(|procedure| {
+            Some((
+                procedure.kind().clone(),
+                procedure.key(),
+                procedure.meta::<T>()?,
+            ))
+        })
+    }
+
+    /// Execute a query directly from Rust without going through a transport.
+    ///
+    /// This runs the procedure through the same middleware stack as a request from the frontend would.
+    pub async fn exec_query<TArg, TResult>(
+        &self,
+        ctx: TCtx,
+        key: impl Into<String>,
+        input: TArg,
+    ) -> Result<TResult, Error>
+    where
+        TArg: Serialize,
+        TResult: DeserializeOwned,
+    {
+        let input = serde_json::to_value(input).map_err(ExecError::SerializingArgErr)?;
+        self.exec_value(ProcedureKind::Query, ctx, key.into(), input)
+            .await
+    }
+
+    /// Execute a mutation directly from Rust without going through a transport.
+    ///
+    /// This runs the procedure through the same middleware stack as a request from the frontend would.
+    pub async fn exec_mutation<TArg, TResult>(
+        &self,
+        ctx: TCtx,
+        key: impl Into<String>,
+        input: TArg,
+    ) -> Result<TResult, Error>
+    where
+        TArg: Serialize,
+        TResult: DeserializeOwned,
+    {
+        let input = serde_json::to_value(input).map_err(ExecError::SerializingArgErr)?;
+        self.exec_value(ProcedureKind::Mutation, ctx, key.into(), input)
+            .await
+    }
+
+    /// Start a subscription directly from Rust without going through a transport.
+    ///
+    /// The returned stream yields each event of the subscription until it ends or is dropped.
+    pub async fn exec_subscription<'a, TArg, TResult>(
+        &'a self,
+        ctx: TCtx,
+        key: impl Into<String>,
+        input: TArg,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<TResult, Error>> + Send + 'a>>, Error>
+    where
+        TArg: Serialize,
+        TResult: DeserializeOwned + Send + 'a,
+    {
+        let input = serde_json::to_value(input).map_err(ExecError::SerializingArgErr)?;
+        let stream = self
+            .exec_stream(ProcedureKind::Subscription, ctx, key.into(), input, None)
+            .await?;
+
+        Ok(Box::pin(stream.map(|v| {
+            v.and_then(|v| serde_json::from_value(v).map_err(ExecError::DeserializingResultErr))
+                .map_err(Into::into)
+        })))
+    }
+
+    async fn exec_value<TResult: DeserializeOwned>(
+        &self,
+        kind: ProcedureKind,
+        ctx: TCtx,
+        key: String,
+        input: Value,
+    ) -> Result<TResult, Error> {
+        let value = self.exec_raw(kind, ctx, key, input).await?;
+        Ok(serde_json::from_value(value).map_err(ExecError::DeserializingResultErr)?)
+    }
+
+    /// Execute a query or mutation and return it's result, applying the procedure's timeout.
+    pub(crate) async fn exec_raw(
+        &self,
+        kind: ProcedureKind,
+        ctx: TCtx,
+        key: String,
+        input: Value,
+    ) -> Result<Value, ExecError> {
+        let kind_str = kind.to_str();
+        let op = self.procedure(&kind, &key)?;
+
+        with_timeout(self.timeout_of(op), async move {
+            let mut stream = op
+                .exec
+                .call(
+                    ctx,
+                    input,
+                    RequestContext {
+                        kind,
+                        path: key,
+                        meta: op.options.meta.clone(),
+                        last_event_id: None,
+                    },
+                )
+                .await?;
+
+            // // TODO: Middleware could mess with this assumption so think about that.
+            // match stream.size_hint() {
+            //     (1, Some(1)) => {}
+            //     hint => {
+            //         #[cfg(debug_assertions)]
+            //         panic!("Internal rspc errror: invalid size hint {:?}", hint);
+            //         #[cfg(not(debug_assertions))]
+            //         return;
+            //     }
+            // }
+
+            match stream.next().await {
+                Some(result) => result,
+                None => {
+                    tracing::warn!("Stream ended unexpectedly for {} operation", kind_str);
+                    Err(ExecError::Internal("Stream ended unexpectedly".to_string()))
+                }
+            }
+        })
+        .await
+    }
+
+    /// Start executing a procedure and return it's stream of results.
+    /// The procedure's timeout only applies to setting up the stream, not to the events it yields.
+    pub(crate) async fn exec_stream(
+        &self,
+        kind: ProcedureKind,
+        ctx: TCtx,
+        key: String,
+        input: Value,
+        last_event_id: Option<u64>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<Value, ExecError>> + Send + '_>>, ExecError> {
+        let op = self.procedure(&kind, &key)?;
+
+        with_timeout(
+            self.timeout_of(op),
+            op.exec.call(
+                ctx,
+                input,
+                RequestContext {
+                    kind,
+                    path: key,
+                    meta: op.options.meta.clone(),
+                    last_event_id,
+                },
+            ),
+        )
+        .await
+    }
+
+    fn procedure(&self, kind: &ProcedureKind, key: &str) -> Result<&Procedure<TCtx>, ExecError> {
+        let store = match kind {
+            ProcedureKind::Query => &self.queries,
+            ProcedureKind::Mutation => &self.mutations,
+            ProcedureKind::Subscription => &self.subscriptions,
+        };
+
+        let op = store
+            .store
+            .get(key)
+            .ok_or_else(|| ExecError::OperationNotFound(key.to_string()))?;
+
+        if let Some(reason) = &op.options.docs.deprecated {
+            tracing::warn!("Called deprecated {} '{}': {}", kind.to_str(), key, reason);
+        }
+
+        Ok(op)
+    }
+
+    fn timeout_of(&self, op: &Procedure<TCtx>) -> Option<Duration> {
+        op.options.timeout.or(self.config.default_timeout)
+    }
+
+    /// export the TypeScript bindings of the router to a file.
+    /// When [Config::split_ts_bindings](crate::Config::split_ts_bindings) is set the path is used as a directory instead
+    /// and the files of prefixes which no longer exist are removed.
+    /// Files are not written if their contents are already up to date so file watchers aren't triggered for nothing.
     pub fn export_ts<TPath: AsRef<Path>>(&self, export_path: TPath) -> Result<(), ExportError> {
-        let export_path = PathBuf::from(export_path.as_ref());
-        if let Some(export_dir) = export_path.parent() {
-            fs::create_dir_all(export_dir)?;
+        let files = self.render_ts(export_path.as_ref())?;
+        if self.config.ts_split {
+            for path in orphaned_files(export_path.as_ref(), &files)? {
+                fs::remove_file(path)?;
+            }
         }
-        let mut file = File::create(export_path)?;
-        if let Some(header) = &self.config.bindings_header {
-            writeln!(file, "{}", header)?;
+
+        for (path, bindings) in files {
+            if matches!(fs::read(&path), Ok(existing) if existing == bindings) {
+                continue;
+            }
+
+            if let Some(export_dir) = path.parent() {
+                fs::create_dir_all(export_dir)?;
+            }
+            File::create(path)?.write_all(&bindings)?;
         }
-        writeln!(file, "// This file was generated by [rspc](https://github.com/spacedriveapp/rspc). Do not edit this file manually.")?;
 
-        let config = Typescript::default().bigint(BigIntExportBehavior::BigInt);
+        Ok(())
+    }
+
+    /// check that the TypeScript bindings at the given path are the same as what [Router::export_ts] would write.
+    /// This is useful in a test so CI fails when someone forgets to commit the regenerated bindings.
+    pub fn check_ts<TPath: AsRef<Path>>(&self, export_path: TPath) -> Result<(), ExportError> {
+        let files = self.render_ts(export_path.as_ref())?;
+        if self.config.ts_split {
+            if let Some(path) = orphaned_files(export_path.as_ref(), &files)?
+                .into_iter()
+                .next()
+            {
+                return Err(ExportError::BindingsOrphaned { path });
+            }
+        }
 
-        let queries_ts =
-            generate_procedures_ts(&config, self.queries.store.iter(), &self.typ_store());
-        let mutations_ts =
-            generate_procedures_ts(&config, self.mutations.store.iter(), &self.typ_store());
-        let subscriptions_ts =
-            generate_procedures_ts(&config, self.subscriptions.store.iter(), &self.typ_store());
+        for (path, bindings) in files {
+            check_file(path, &String::from_utf8_lossy(&bindings))?;
+        }
 
-        // TODO: Specta API
+        Ok(())
+    }
+
+    /// render every file written by [Router::export_ts] with the path it's written to.
+    fn render_ts(&self, export_path: &Path) -> Result<Vec<(PathBuf, Vec<u8>)>, ExportError> {
+        // Declared types are meant for ambient `.d.ts` files which can't contain runtime code.
+        if self.config.ts_declaration_style == TsDeclarationStyle::Declare
+            && (self.config.zod_schemas || self.config.ts_client)
+        {
+            return Err(ExportError::DeclareStyleRuntimeExport);
+        }
+
+        let mut config = Typescript::default().bigint(self.config.ts_bigint.clone());
+        if let Some(style) = self.config.ts_comment_style {
+            config = config.comment_style(style);
+        }
+
+        let files = match self.config.ts_split {
+            true => self.render_ts_split(&config, export_path)?,
+            false => vec![(export_path.to_path_buf(), self.render_ts_file(&config)?)],
+        };
+
+        files
+            .into_iter()
+            .map(|(path, file)| {
+                let file = self.format_ts(&path, file)?;
+                Ok((path, file))
+            })
+            .collect()
+    }
+
+    fn render_ts_file(&self, config: &Typescript) -> Result<Vec<u8>, ExportError> {
+        let mut file = self.ts_header()?;
+        if self.config.ts_client {
+            writeln!(
+                file,
+                "\nimport type {{ AlphaClient, SubscriptionOptions }} from \"@rspc/client\";"
+            )?;
+        }
+        if self.config.zod_schemas {
+            writeln!(file, "\nimport {{ z }} from \"zod\";")?;
+        }
+
+        writeln!(file, "\n{}", self.procedures_ts(config, |_| true))?;
+        write!(file, "{}", self.named_types_ts(config)?)?;
+
+        if self.config.ts_client {
+            write!(file, "{}", self.client_ts(config)?)?;
+        }
+
+        if self.config.zod_schemas {
+            write!(file, "{}", self.zod_ts())?;
+        }
+
+        Ok(file)
+    }
+
+    pub(crate) fn ts_header(&self) -> Result<Vec<u8>, ExportError> {
+        let mut file = Vec::new();
+        if let Some(header) = &self.config.bindings_header {
+            writeln!(file, "{}", header)?;
+        }
         writeln!(
             file,
-            r#"
-export type Procedures = {{
+            "// {}. Do not edit this file manually.",
+            GENERATED_MARKER
+        )?;
+        Ok(file)
+    }
+
+    /// the `Procedures` type containing every procedure which matches the filter.
+    pub(crate) fn procedures_ts(
+        &self,
+        config: &Typescript,
+        filter: impl Fn(&Procedure<TCtx>) -> bool,
+    ) -> String {
+        let procedures = |store: &ProcedureStore<TCtx>| {
+            generate_procedures_ts(
+                config,
+                store
+                    .store
+                    .iter()
+                    .filter(|(_, procedure)| filter(procedure))
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+                &self.typ_store,
+            )
+        };
+        let queries_ts = procedures(&self.queries);
+        let mutations_ts = procedures(&self.mutations);
+        let subscriptions_ts = procedures(&self.subscriptions);
+
+        // TODO: Specta API
+        format!(
+            r#"{} Procedures = {{
     queries: {queries_ts},
     mutations: {mutations_ts},
     subscriptions: {subscriptions_ts}
-}};"#
-        )?;
+}};"#,
+            self.ts_declaration()
+        )
+    }
 
+    /// every named type in the type map. Each type is surrounded by new lines.
+    pub(crate) fn named_types_ts(&self, config: &Typescript) -> Result<String, ExportError> {
         if let Some((name, a, b)) = detect_duplicate_type_names(&self.typ_store)
             .into_iter()
             .by_ref()
@@ -96,15 +405,117 @@ export type Procedures = {{
             ));
         }
 
+        let mut out = String::new();
         for (_sid, dt) in self.typ_store.iter() {
-            writeln!(
-                file,
-                "\n{}",
-                export_named_datatype(&config, dt, &self.typ_store)?
-            )?;
+            let ts = export_named_datatype(config, dt, &self.typ_store)?;
+            let ts = match self.config.ts_declaration_style {
+                TsDeclarationStyle::Export => ts,
+                // Specta always exports with `export type`. Only the start of the line is rewritten so doc comments containing it are kept as is.
+                TsDeclarationStyle::Declare => ts
+                    .lines()
+                    .map(|line| match line.strip_prefix("export type ") {
+                        Some(declaration) => format!("{} {}", self.ts_declaration(), declaration),
+                        None => line.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            };
+            out.push_str(&format!("\n{}\n", ts));
         }
+        Ok(out)
+    }
 
-        Ok(())
+    /// the keyword types are declared with in the Typescript bindings. Refer to [TsDeclarationStyle].
+    pub(crate) fn ts_declaration(&self) -> &'static str {
+        match self.config.ts_declaration_style {
+            TsDeclarationStyle::Export => "export type",
+            TsDeclarationStyle::Declare => "declare type",
+        }
+    }
+
+    fn format_ts(&self, export_path: &Path, file: Vec<u8>) -> Result<Vec<u8>, ExportError> {
+        let Some(formatter) = self.config.ts_formatter else {
+            return Ok(file);
+        };
+
+        // Formatters work on files so the bindings are formatted in a temporary file next to the export path.
+        // This way the formatter picks up the same config as it would for the bindings.
+        let tmp_path = export_path.with_file_name(format!(
+            ".{}.rspc.ts",
+            export_path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("bindings")
+        ));
+        if let Some(export_dir) = tmp_path.parent() {
+            fs::create_dir_all(export_dir)?;
+        }
+        let tmp_file = TempFile(tmp_path);
+        fs::write(&tmp_file.0, &file)?;
+        formatter(&tmp_file.0)?;
+        Ok(fs::read(&tmp_file.0)?)
+    }
+}
+
+/// A file which is removed when this is dropped, so it's cleaned up when writing, formatting or reading it fails or panics.
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        fs::remove_file(&self.0).ok();
+    }
+}
+
+/// Compare a file on disk to it's expected contents, returning where they first differ.
+fn check_file(path: PathBuf, expected: &str) -> Result<(), ExportError> {
+    let existing = match fs::read_to_string(&path) {
+        Ok(existing) => existing,
+        // A missing file is reported as a mismatch on the first line.
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut expected_lines = expected.lines();
+    let mut found_lines = existing.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), found_lines.next()) {
+            (None, None) => break,
+            (Some(a), Some(b)) if a == b => line += 1,
+            (a, b) => {
+                return Err(ExportError::BindingsOutdated {
+                    path,
+                    line,
+                    expected: a.unwrap_or("<end of file>").to_string(),
+                    found: b.unwrap_or("<end of file>").to_string(),
+                })
+            }
+        }
+    }
+
+    // `lines` ignores a trailing newline so compare the whole file to catch that as well.
+    if expected != existing {
+        return Err(ExportError::BindingsOutdated {
+            path,
+            line,
+            expected: "<end of file>".into(),
+            found: "<end of file>".into(),
+        });
+    }
+
+    Ok(())
+}
+
+async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    fut: impl Future<Output = Result<T, ExecError>>,
+) -> Result<T, ExecError> {
+    match timeout {
+        // When the timeout elapses `fut` is dropped which cancels the resolver.
+        Some(timeout) => tokio::time::timeout(timeout, fut)
+            .await
+            .unwrap_or(Err(ExecError::ErrTimeout)),
+        None => fut.await,
     }
 }
 
@@ -134,14 +545,316 @@ fn generate_procedures_ts<'a, Ctx: 'a>(
                     type_store,
                 )
                 .expect("Failed to generate TypeScript type for procedure result");
+                let error_ts = match &operation.ty.error {
+                    // This is the case for procedures which return `rspc::Error` as it has no typed data.
+                    DataType::Tuple(def) if def.elements().is_empty() => "never".into(),
+                    ty => datatype(
+                        config,
+                        &FunctionResultVariant::Value(ty.clone()),
+                        type_store,
+                    )
+                    .expect("Failed to generate TypeScript type for procedure error"),
+                };
 
                 // TODO: Specta API
                 format!(
                     r#"
-        {{ key: "{key}", input: {input}, result: {result_ts} }}"#
+        {}{{ key: "{key}", input: {input}, result: {result_ts}, error: {error_ts} }}"#,
+                    jsdoc(&operation.options.docs, "        ")
                 )
             })
             .collect::<Vec<_>>()
             .join(" | "),
     }
 }
+
+/// Render the description and deprecation notice of a procedure as a JSDoc comment followed by a new line and the indentation.
+/// Returns an empty string if the procedure has neither.
+pub(crate) fn jsdoc(docs: &ProcedureDocs, indent: &str) -> String {
+    let mut lines = Vec::new();
+    if let Some(description) = &docs.description {
+        lines.extend(description.lines().map(str::to_string));
+    }
+    if let Some(reason) = &docs.deprecated {
+        lines.push(format!("@deprecated {}", reason));
+    }
+
+    match lines.is_empty() {
+        true => String::new(),
+        false => format!(
+            "/**\n{}{indent} */\n{indent}",
+            lines
+                .iter()
+                // `*/` would end the comment early
+                .map(|line| format!("{indent} * {}\n", line.replace("*/", "*\\/")))
+                .collect::<String>()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, time::Duration};
+
+    use futures::StreamExt;
+
+    use crate::{Config, ErrorCode, ExportError, Router, TsDeclarationStyle};
+
+    fn router() -> Router<()> {
+        Router::<()>::new()
+            .query("double", |t| t(|_, n: i32| Ok(n * 2)))
+            .mutation("double", |t| t(|_, n: i32| Ok(n * 2)))
+            .subscription("count", |t| t(|_, n: u32| Ok(futures::stream::iter(0..n))))
+            .build()
+    }
+
+    #[tokio::test]
+    async fn exec_returns_result() {
+        let router = router();
+
+        let result: i32 = router
+            .exec_query((), "double", 2)
+            .await
+            .expect("query failed");
+        assert_eq!(result, 4);
+
+        let result: i32 = router
+            .exec_mutation((), "double", 3)
+            .await
+            .expect("mutation failed");
+        assert_eq!(result, 6);
+
+        let events = router
+            .exec_subscription::<_, u32>((), "count", 3)
+            .await
+            .expect("subscription failed")
+            .map(|event| event.expect("subscription event failed"))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(events, [0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn exec_missing_procedure() {
+        let router = router();
+
+        let err = router
+            .exec_query::<_, i32>((), "missing", 2)
+            .await
+            .expect_err("query should not exist");
+        assert_eq!(err.code, ErrorCode::NotFound);
+
+        // Procedures are only looked up in the store of the requested kind.
+        let err = router
+            .exec_mutation::<_, u32>((), "count", 2)
+            .await
+            .expect_err("mutation should not exist");
+        assert_eq!(err.code, ErrorCode::NotFound);
+
+        let err = router
+            .exec_subscription::<_, u32>((), "double", 2)
+            .await
+            .err()
+            .expect("subscription should not exist");
+        assert_eq!(err.code, ErrorCode::NotFound);
+    }
+
+    #[tokio::test]
+    async fn exec_bad_input() {
+        let router = router();
+
+        let err = router
+            .exec_query::<_, i32>((), "double", "two")
+            .await
+            .expect_err("query should reject the input");
+        assert_eq!(err.code, ErrorCode::BadRequest);
+
+        let err = router
+            .exec_subscription::<_, u32>((), "count", -1)
+            .await
+            .err()
+            .expect("subscription should reject the input");
+        assert_eq!(err.code, ErrorCode::BadRequest);
+    }
+
+    async fn sleep(secs: u64) -> Result<(), crate::Error> {
+        tokio::time::sleep(Duration::from_secs(secs)).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn slow_procedure_times_out() {
+        tokio::time::pause();
+        let router = Router::<()>::new()
+            .query("slow", |t| {
+                t(|_, _: ()| sleep(10)).timeout(Duration::from_secs(1))
+            })
+            .query("fast", |t| {
+                t(|_, _: ()| sleep(1)).timeout(Duration::from_secs(10))
+            })
+            .build();
+
+        let err = router
+            .exec_query::<_, ()>((), "slow", ())
+            .await
+            .expect_err("query should time out");
+        assert_eq!(err.code, ErrorCode::Timeout);
+
+        router
+            .exec_query::<_, ()>((), "fast", ())
+            .await
+            .expect("query shouldn't time out");
+    }
+
+    #[tokio::test]
+    async fn router_timeout_applies_through_merge() {
+        tokio::time::pause();
+        let router = Router::<()>::new()
+            .config(Config::new().default_timeout(Duration::from_secs(100)))
+            .merge(
+                "users",
+                Router::<()>::new()
+                    .timeout(Duration::from_secs(1))
+                    .query("slow", |t| t(|_, _: ()| sleep(10))),
+            )
+            .query("slow", |t| t(|_, _: ()| sleep(10)))
+            .build();
+
+        let err = router
+            .exec_query::<_, ()>((), "users.slow", ())
+            .await
+            .expect_err("merged query should use the timeout of it's router");
+        assert_eq!(err.code, ErrorCode::Timeout);
+
+        // The timeout of the merged router doesn't leak into the router it was merged into.
+        router
+            .exec_query::<_, ()>((), "slow", ())
+            .await
+            .expect("query should use the default timeout");
+    }
+
+    #[tokio::test]
+    async fn procedure_timeout_overrides_router_timeout() {
+        tokio::time::pause();
+        let router = Router::<()>::new()
+            .merge(
+                "users",
+                Router::<()>::new()
+                    .timeout(Duration::from_secs(1))
+                    .query("slow", |t| {
+                        t(|_, _: ()| sleep(10)).timeout(Duration::from_secs(20))
+                    }),
+            )
+            .build();
+
+        router
+            .exec_query::<_, ()>((), "users.slow", ())
+            .await
+            .expect("query should use it's own timeout");
+    }
+
+    #[test]
+    fn format_ts_removes_temp_file_on_failure() {
+        let dir = std::env::temp_dir().join(format!("rspc-format-ts-{}", std::process::id()));
+        let router = Router::<()>::new()
+            .config(Config::new().ts_formatter(|_| Err(std::io::ErrorKind::Other.into())))
+            .build();
+
+        assert!(router.export_ts(dir.join("bindings.ts")).is_err());
+        let leftover = std::fs::read_dir(&dir)
+            .map(|entries| entries.count())
+            .unwrap_or(0);
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(leftover, 0);
+    }
+
+    #[derive(serde::Serialize, specta::Type)]
+    struct User {
+        id: i32,
+    }
+
+    #[test]
+    fn declare_style() {
+        let build = |config: Config| {
+            Router::<()>::new()
+                .config(config.ts_declaration_style(TsDeclarationStyle::Declare))
+                .query("user", |t| t(|_, _: ()| Ok(User { id: 1 })))
+                .build()
+        };
+
+        let files = build(Config::new())
+            .render_ts(Path::new("bindings.d.ts"))
+            .expect("failed to render bindings");
+        let bindings = String::from_utf8_lossy(&files[0].1);
+        assert!(bindings.contains("declare type Procedures = "));
+        assert!(bindings.contains("declare type User = "));
+        assert!(!bindings.contains("export "));
+
+        assert!(matches!(
+            build(Config::new().export_zod_schemas()).render_ts(Path::new("bindings.d.ts")),
+            Err(ExportError::DeclareStyleRuntimeExport)
+        ));
+        assert!(matches!(
+            build(Config::new().export_ts_client()).render_ts(Path::new("bindings.d.ts")),
+            Err(ExportError::DeclareStyleRuntimeExport)
+        ));
+    }
+
+    #[test]
+    fn split_bindings_use_merge_prefix() {
+        let dir = std::env::temp_dir().join(format!("rspc-split-ts-{}", std::process::id()));
+        let router = Router::<()>::new()
+            .config(Config::new().split_ts_bindings())
+            .query("version", |t| t(|_, _: ()| Ok(1)))
+            .merge(
+                "users",
+                Router::<()>::new()
+                    .query("get", |t| t(|_, _: ()| Ok(User { id: 1 })))
+                    .merge(
+                        "admin",
+                        Router::<()>::new().mutation("ban", |t| t(|_, _: i32| Ok(()))),
+                    ),
+            )
+            .build();
+
+        let mut files = router
+            .render_ts(&dir)
+            .expect("failed to render bindings")
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        files.sort();
+        let procedures = dir.join("procedures");
+        assert_eq!(
+            files,
+            [
+                dir.join("index.ts"),
+                procedures.join("_root.ts"),
+                procedures.join("users.admin.ts"),
+                procedures.join("users.ts"),
+                dir.join("types.ts"),
+            ]
+        );
+
+        // A file of a prefix which no longer exists is reported and removed. Files rspc didn't generate are left alone.
+        std::fs::create_dir_all(&procedures).expect("failed to create directory");
+        let handwritten = procedures.join("handwritten.ts");
+        std::fs::write(&handwritten, "export {};").expect("failed to write file");
+        router.export_ts(&dir).expect("failed to export bindings");
+
+        let orphaned = procedures.join("posts.ts");
+        let header = router.ts_header().expect("failed to render header");
+        std::fs::write(&orphaned, header).expect("failed to write file");
+        let check = router.check_ts(&dir);
+        router.export_ts(&dir).expect("failed to export bindings");
+        let removed = !orphaned.exists();
+        let kept = handwritten.exists();
+        let up_to_date = router.check_ts(&dir);
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(matches!(check, Err(ExportError::BindingsOrphaned { path }) if path == orphaned));
+        assert!(removed);
+        assert!(kept);
+        assert!(up_to_date.is_ok());
+    }
+}
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, marker::PhantomData, panic::Location};
+use std::{borrow::Cow, marker::PhantomData, panic::Location, time::Duration};
 
 use serde::de::DeserializeOwned;
 use specta::{Type, TypeMap};
@@ -7,10 +7,11 @@ use specta_typescript as ts;
 use crate::{
     internal::{
         BaseMiddleware, BuiltProcedureBuilder, EitherLayer, MiddlewareBuilderLike,
-        MiddlewareLayerBuilder, ProcedureDataType, ProcedureStore, ResolverLayer,
+        MiddlewareLayerBuilder, ProcedureDataType, ProcedureOptions, ProcedureStore, ResolverLayer,
         UnbuiltProcedureBuilder,
     },
-    Config, ExecError, MiddlewareBuilder, MiddlewareLike, RequestLayer, Router, StreamRequestLayer,
+    Config, ExecError, MiddlewareBuilder, MiddlewareLike, RequestLayer, ResolverError, Router,
+    StreamRequestLayer,
 };
 
 // TODO: Storing procedure names as an `ThinVec<Cow<'static, str>>` instead.
@@ -66,6 +67,7 @@ pub struct RouterBuilder<
     pub(crate) mutations: ProcedureStore<TCtx>,
     pub(crate) subscriptions: ProcedureStore<TCtx>,
     pub(crate) typ_store: TypeMap,
+    pub(crate) timeout: Option<Duration>,
     pub(crate) phantom: PhantomData<TMeta>,
 }
 
@@ -118,6 +120,7 @@ where
             mutations: ProcedureStore::new("mutation"),
             subscriptions: ProcedureStore::new("subscription"),
             typ_store: TypeMap::default(),
+            timeout: None,
             phantom: PhantomData,
         }
     }
@@ -166,6 +169,14 @@ where
         self
     }
 
+    /// Set the timeout for all procedures of this router which don't set their own.
+    /// When this router is merged into another one, this applies to every procedure under its prefix.
+    /// For subscriptions this only applies to setting up the stream, not to the events it yields.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn middleware<TNewMiddleware, TNewLayerCtx>(
         self,
         builder: impl Fn(MiddlewareBuilder<TLayerCtx>) -> TNewMiddleware,
@@ -185,6 +196,7 @@ where
             mutations,
             subscriptions,
             typ_store,
+            timeout,
             ..
         } = self;
 
@@ -200,6 +212,7 @@ where
             mutations,
             subscriptions,
             typ_store,
+            timeout,
             phantom: PhantomData,
         }
     }
@@ -209,8 +222,8 @@ where
         mut self,
         key: &'static str,
         builder: impl FnOnce(
-            UnbuiltProcedureBuilder<TLayerCtx, TResolver>,
-        ) -> BuiltProcedureBuilder<TResolver>,
+            UnbuiltProcedureBuilder<TLayerCtx, TResolver, TMeta>,
+        ) -> BuiltProcedureBuilder<TResolver, TMeta>,
     ) -> Self
     where
         TArg: DeserializeOwned + Type,
@@ -224,7 +237,13 @@ where
             );
         }
 
-        let resolver = builder(UnbuiltProcedureBuilder::default()).resolver;
+        let BuiltProcedureBuilder {
+            resolver,
+            timeout,
+            meta,
+            docs,
+            ..
+        } = builder(UnbuiltProcedureBuilder::default());
         self.queries.append(
             key.into(),
             self.middleware.build(ResolverLayer {
@@ -237,13 +256,23 @@ where
                 },
                 phantom: PhantomData,
             }),
-            typedef::<TArg, TResult::Result>(Cow::Borrowed(key), &mut self.typ_store)
-                .unwrap_or_else(|_| {
-                    panic!(
-                        "{}: Failed to generate type definition for query",
-                        Location::caller()
-                    )
-                }),
+            typedef::<TArg, TResult::Result, TResult::Error>(
+                Cow::Borrowed(key),
+                &mut self.typ_store,
+            )
+            .unwrap_or_else(|_| {
+                panic!(
+                    "{}: Failed to generate type definition for query",
+                    Location::caller()
+                )
+            }),
+            ProcedureOptions {
+                timeout,
+                meta,
+                location: Location::caller(),
+                docs,
+                prefix: String::new(),
+            },
         );
         self
     }
@@ -253,8 +282,8 @@ where
         mut self,
         key: &'static str,
         builder: impl FnOnce(
-            UnbuiltProcedureBuilder<TLayerCtx, TResolver>,
-        ) -> BuiltProcedureBuilder<TResolver>,
+            UnbuiltProcedureBuilder<TLayerCtx, TResolver, TMeta>,
+        ) -> BuiltProcedureBuilder<TResolver, TMeta>,
     ) -> Self
     where
         TArg: DeserializeOwned + Type,
@@ -268,7 +297,13 @@ where
             );
         }
 
-        let resolver = builder(UnbuiltProcedureBuilder::default()).resolver;
+        let BuiltProcedureBuilder {
+            resolver,
+            timeout,
+            meta,
+            docs,
+            ..
+        } = builder(UnbuiltProcedureBuilder::default());
         self.mutations.append(
             key.into(),
             self.middleware.build(ResolverLayer {
@@ -281,13 +316,23 @@ where
                 },
                 phantom: PhantomData,
             }),
-            typedef::<TArg, TResult::Result>(Cow::Borrowed(key), &mut self.typ_store)
-                .unwrap_or_else(|_| {
-                    panic!(
-                        "{}: Failed to generate type definition for mutation",
-                        Location::caller()
-                    )
-                }),
+            typedef::<TArg, TResult::Result, TResult::Error>(
+                Cow::Borrowed(key),
+                &mut self.typ_store,
+            )
+            .unwrap_or_else(|_| {
+                panic!(
+                    "{}: Failed to generate type definition for mutation",
+                    Location::caller()
+                )
+            }),
+            ProcedureOptions {
+                timeout,
+                meta,
+                location: Location::caller(),
+                docs,
+                prefix: String::new(),
+            },
         );
         self
     }
@@ -296,7 +341,9 @@ where
     pub fn subscription<F, TArg, TResult, TResultMarker>(
         mut self,
         key: &'static str,
-        builder: impl FnOnce(UnbuiltProcedureBuilder<TLayerCtx, F>) -> BuiltProcedureBuilder<F>,
+        builder: impl FnOnce(
+            UnbuiltProcedureBuilder<TLayerCtx, F, TMeta>,
+        ) -> BuiltProcedureBuilder<F, TMeta>,
     ) -> Self
     where
         F: Fn(TLayerCtx, TArg) -> TResult + Send + Sync + 'static,
@@ -310,7 +357,13 @@ where
             );
         }
 
-        let resolver = builder(UnbuiltProcedureBuilder::default()).resolver;
+        let BuiltProcedureBuilder {
+            resolver,
+            timeout,
+            meta,
+            docs,
+            ..
+        } = builder(UnbuiltProcedureBuilder::default());
         self.subscriptions.append(
             key.into(),
             self.middleware.build(ResolverLayer {
@@ -323,13 +376,23 @@ where
                 },
                 phantom: PhantomData,
             }),
-            typedef::<TArg, TResult::Result>(Cow::Borrowed(key), &mut self.typ_store)
-                .unwrap_or_else(|_| {
-                    panic!(
-                        "{}: Failed to generate type definition for subscription",
-                        Location::caller()
-                    )
-                }),
+            typedef::<TArg, TResult::Result, TResult::Error>(
+                Cow::Borrowed(key),
+                &mut self.typ_store,
+            )
+            .unwrap_or_else(|_| {
+                panic!(
+                    "{}: Failed to generate type definition for subscription",
+                    Location::caller()
+                )
+            }),
+            ProcedureOptions {
+                timeout,
+                meta,
+                location: Location::caller(),
+                docs,
+                prefix: String::new(),
+            },
         );
         self
     }
@@ -366,6 +429,7 @@ where
                         format!("{}{}", prefix, key),
                         self.middleware.build(exec),
                         query.ty,
+                        query.options.merged(&prefix, router.timeout),
                     );
                 }
                 #[cfg(feature = "alpha")]
@@ -381,6 +445,7 @@ where
                         format!("{}{}", prefix, key),
                         self.middleware.build(exec),
                         mutation.ty,
+                        mutation.options.merged(&prefix, router.timeout),
                     );
                 }
                 #[cfg(feature = "alpha")]
@@ -397,6 +462,7 @@ where
                         format!("{}{}", prefix, key),
                         self.middleware.build(exec),
                         subscription.ty,
+                        subscription.options.merged(&prefix, router.timeout),
                     );
                 }
                 #[cfg(feature = "alpha")]
@@ -414,13 +480,18 @@ where
     pub fn build(self) -> Router<TCtx, TMeta> {
         let Self {
             config,
-            queries,
-            mutations,
-            subscriptions,
+            mut queries,
+            mut mutations,
+            mut subscriptions,
             typ_store,
+            timeout,
             ..
         } = self;
 
+        queries.default_timeout(timeout);
+        mutations.default_timeout(timeout);
+        subscriptions.default_timeout(timeout);
+
         let export_path = config.export_bindings_on_build.clone();
         let router = Router {
             config,
@@ -444,7 +515,7 @@ where
 
 // #[deprecated = "Removed in v1.0.0. Is now `<TResolver as ResolverFunction<_>>::typedef`"]
 #[doc(hidden)]
-pub fn typedef<TArg: Type, TResult: Type>(
+pub fn typedef<TArg: Type, TResult: Type, TError: ResolverError>(
     key: Cow<'static, str>,
     type_map: &mut TypeMap,
 ) -> Result<ProcedureDataType, ts::ExportError> {
@@ -452,5 +523,6 @@ pub fn typedef<TArg: Type, TResult: Type>(
         key,
         input: <TArg as Type>::reference(type_map, &[]).inner,
         result: <TResult as Type>::reference(type_map, &[]).inner,
+        error: TError::typedef(type_map),
     })
 }
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use specta::{datatype::FunctionResultVariant, DataType};
+use specta_typescript::{datatype, Typescript};
+
+use crate::{
+    internal::ProcedureKind, json_schema::is_unit, router::jsdoc, ExportError, ProcedureInfo,
+    Router,
+};
+
+enum Node<'a> {
+    Branch(BTreeMap<&'a str, Node<'a>>),
+    Procedure(ProcedureInfo<'a>),
+}
+
+impl<TCtx, TMeta> Router<TCtx, TMeta>
+where
+    TCtx: Send + 'static,
+{
+    /// generate the `createApi` function which wraps an `AlphaClient` in an object with one typed function per procedure.
+    /// The object is nested by the dotted prefixes of the procedure keys, so `users.get` is called as `api.users.get(input)`.
+    /// This is appended to the TypeScript bindings when [Config::export_ts_client](crate::Config::export_ts_client) is set.
+    pub(crate) fn client_ts(&self, config: &Typescript) -> Result<String, ExportError> {
+        let mut root = BTreeMap::new();
+        for procedure in self.procedures() {
+            let key = procedure.key();
+            let mut segments = key.split('.').peekable();
+            let mut node = &mut root;
+            while let Some(segment) = segments.next() {
+                if segments.peek().is_none() {
+                    if node.insert(segment, Node::Procedure(procedure)).is_some() {
+                        return Err(ExportError::ClientKeyConflict(key.to_string()));
+                    }
+                    break;
+                }
+
+                node = match node
+                    .entry(segment)
+                    .or_insert_with(|| Node::Branch(BTreeMap::new()))
+                {
+                    Node::Branch(children) => children,
+                    Node::Procedure(_) => {
+                        return Err(ExportError::ClientKeyConflict(key.to_string()))
+                    }
+                };
+            }
+        }
+
+        let mut out =
+            String::from("\nexport function createApi(client: AlphaClient<any>) {\n    return ");
+        self.render_branch(config, &root, 1, &mut out)?;
+        out.push_str(";\n}\n");
+        Ok(out)
+    }
+
+    fn render_branch(
+        &self,
+        config: &Typescript,
+        children: &BTreeMap<&str, Node<'_>>,
+        depth: usize,
+        out: &mut String,
+    ) -> Result<(), ExportError> {
+        let indent = "    ".repeat(depth + 1);
+        out.push_str("{\n");
+        for (name, node) in children {
+            match node {
+                Node::Branch(children) => {
+                    out.push_str(&format!("{}{}: ", indent, property(name)));
+                    self.render_branch(config, children, depth + 1, out)?;
+                    out.push_str(",\n");
+                }
+                Node::Procedure(procedure) => {
+                    out.push_str(&format!(
+                        "{indent}{}{}: {},\n",
+                        jsdoc(procedure.docs(), &indent),
+                        property(name),
+                        self.procedure_fn(config, procedure)?,
+                    ));
+                }
+            }
+        }
+        out.push_str(&"    ".repeat(depth));
+        out.push('}');
+        Ok(())
+    }
+
+    fn procedure_fn(
+        &self,
+        config: &Typescript,
+        procedure: &ProcedureInfo<'_>,
+    ) -> Result<String, ExportError> {
+        let ts = |dt: &DataType| {
+            datatype(
+                config,
+                &FunctionResultVariant::Value(dt.clone()),
+                &self.typ_store,
+            )
+        };
+
+        let key = serde_json::Value::from(procedure.key()).to_string();
+        let result = ts(procedure.result())?;
+        // This matches the `never` input of the procedure in the `Procedures` type.
+        let (param, key_and_input) = match is_unit(procedure.input()) {
+            true => (String::new(), format!("[{}]", key)),
+            false => (
+                format!("input: {}, ", ts(procedure.input())?),
+                format!("[{}, input]", key),
+            ),
+        };
+
+        Ok(match procedure.kind() {
+            ProcedureKind::Query => format!(
+                "({param}opts?: {{ signal?: AbortSignal }}) => client.query({key_and_input}, opts) as Promise<{result}>"
+            ),
+            ProcedureKind::Mutation => format!(
+                "({param}opts?: {{ signal?: AbortSignal }}) => client.mutation({key_and_input}, opts) as Promise<{result}>"
+            ),
+            ProcedureKind::Subscription => format!(
+                "({param}opts: SubscriptionOptions<{result}>) => client.addSubscription({key_and_input}, opts)"
+            ),
+        })
+    }
+}
+
+/// Segments which aren't valid JavaScript identifiers must be quoted.
+fn property(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_ident = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+
+    match is_ident {
+        true => name.to_string(),
+        false => serde_json::Value::from(name).to_string(),
+    }
+}
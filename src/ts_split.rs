@@ -0,0 +1,280 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use specta::{datatype::Fields, DataType, TypeMap};
+use specta_typescript::Typescript;
+
+use crate::{router::GENERATED_MARKER, ExportError, ProcedureInfo, Router, TsDeclarationStyle};
+
+/// The namespace of procedures which weren't merged in with a prefix.
+const ROOT_NAMESPACE: &str = "_root";
+
+impl<TCtx, TMeta> Router<TCtx, TMeta>
+where
+    TCtx: Send + 'static,
+{
+    /// render the bindings as a directory of files. Used when [Config::split_ts_bindings](crate::Config::split_ts_bindings) is set.
+    ///
+    /// - `types.ts` contains every named type (and the Zod schemas if enabled).
+    /// - `procedures/<prefix>.ts` contains a `Procedures` type for each prefix the routers were merged with.
+    /// - `index.ts` re-exports the types and combines the `Procedures` types (and the client if enabled).
+    pub(crate) fn render_ts_split(
+        &self,
+        config: &Typescript,
+        export_dir: &Path,
+    ) -> Result<Vec<(PathBuf, Vec<u8>)>, ExportError> {
+        if self.config.ts_declaration_style == TsDeclarationStyle::Declare {
+            return Err(ExportError::SplitDeclareStyle);
+        }
+
+        let namespaces = self
+            .procedures()
+            .map(|procedure| namespace(procedure.prefix()))
+            .collect::<BTreeSet<_>>();
+        self.check_namespaces(&namespaces)?;
+        let mut files = Vec::new();
+
+        let mut types = self.ts_header()?;
+        if self.config.zod_schemas {
+            writeln!(types, "\nimport {{ z }} from \"zod\";")?;
+        }
+        write!(types, "{}", self.named_types_ts(config)?)?;
+        if self.config.zod_schemas {
+            write!(types, "{}", self.zod_ts())?;
+        }
+        files.push((export_dir.join("types.ts"), types));
+
+        for ns in &namespaces {
+            let mut file = self.ts_header()?;
+            write_type_imports(
+                &mut file,
+                self.referenced_types(self.procedures().filter(|p| namespace(p.prefix()) == *ns)),
+                "../types",
+            )?;
+            writeln!(
+                file,
+                "\n{}",
+                self.procedures_ts(config, |p| namespace(&p.options.prefix) == *ns)
+            )?;
+            files.push((
+                export_dir.join("procedures").join(format!("{}.ts", ns)),
+                file,
+            ));
+        }
+
+        let mut index = self.ts_header()?;
+        if self.config.ts_client {
+            writeln!(
+                index,
+                "\nimport type {{ AlphaClient, SubscriptionOptions }} from \"@rspc/client\";"
+            )?;
+            write_type_imports(
+                &mut index,
+                self.referenced_types(self.procedures()),
+                "./types",
+            )?;
+        }
+        writeln!(index)?;
+        for ns in &namespaces {
+            writeln!(
+                index,
+                "import type {{ Procedures as {} }} from \"./procedures/{}\";",
+                alias(ns),
+                ns
+            )?;
+        }
+        writeln!(index, "\nexport * from \"./types\";")?;
+
+        let kind = |kind: &str| match namespaces.is_empty() {
+            true => "never".to_string(),
+            false => namespaces
+                .iter()
+                .map(|ns| format!("{}[\"{}\"]", alias(ns), kind))
+                .collect::<Vec<_>>()
+                .join(" | "),
+        };
+        writeln!(
+            index,
+            "\n{}",
+            format!(
+                "export type Procedures = {{\n    queries: {},\n    mutations: {},\n    subscriptions: {}\n}};",
+                kind("queries"),
+                kind("mutations"),
+                kind("subscriptions")
+            )
+        )?;
+
+        if self.config.ts_client {
+            write!(index, "{}", self.client_ts(config)?)?;
+        }
+        files.push((export_dir.join("index.ts"), index));
+
+        Ok(files)
+    }
+
+    /// ensure every namespace gets its own file and import alias. Otherwise they would silently overwrite each other.
+    fn check_namespaces(&self, namespaces: &BTreeSet<&str>) -> Result<(), ExportError> {
+        if self.procedures().any(|p| p.prefix().is_empty())
+            && self.procedures().any(|p| p.prefix() == ROOT_NAMESPACE)
+        {
+            return Err(ExportError::SplitNamespaceConflict(
+                ROOT_NAMESPACE.into(),
+                "(no prefix)".into(),
+            ));
+        }
+
+        let mut aliases = HashMap::new();
+        let mut file_names = HashMap::new();
+        for ns in namespaces {
+            // File names are compared ignoring case as they would overwrite each other on macOS and Windows.
+            for (seen, name) in [
+                (&mut aliases, alias(ns)),
+                (&mut file_names, ns.to_lowercase()),
+            ] {
+                if let Some(other) = seen.insert(name, *ns) {
+                    return Err(ExportError::SplitNamespaceConflict(
+                        other.into(),
+                        (*ns).into(),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// the names of the named types used directly by the inputs, results and errors of the procedures.
+    fn referenced_types<'a>(
+        &'a self,
+        procedures: impl Iterator<Item = ProcedureInfo<'a>>,
+    ) -> BTreeSet<&'a str> {
+        let mut names = BTreeSet::new();
+        for procedure in procedures {
+            for dt in [procedure.input(), procedure.result(), procedure.error()] {
+                collect_references(dt, &self.typ_store, &mut names);
+            }
+        }
+        names
+    }
+}
+
+/// The namespace of a procedure with the given prefix.
+/// Routers merged into an already merged router get their own namespace, Eg. `users.admin`.
+fn namespace(prefix: &str) -> &str {
+    match prefix {
+        "" => ROOT_NAMESPACE,
+        prefix => prefix,
+    }
+}
+
+/// the generated files in `procedures/` which aren't part of the given bindings anymore. Eg. because a router was removed or renamed.
+/// Files which weren't generated by rspc are left alone.
+pub(crate) fn orphaned_files(
+    export_dir: &Path,
+    files: &[(PathBuf, Vec<u8>)],
+) -> Result<Vec<PathBuf>, ExportError> {
+    let entries = match fs::read_dir(export_dir.join("procedures")) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut orphaned = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "ts")
+            || files.iter().any(|(file, _)| *file == path)
+        {
+            continue;
+        }
+
+        if fs::read_to_string(&path)?.contains(GENERATED_MARKER) {
+            orphaned.push(path);
+        }
+    }
+    orphaned.sort();
+    Ok(orphaned)
+}
+
+/// The name the `Procedures` type of a namespace is imported as in `index.ts`.
+fn alias(ns: &str) -> String {
+    let mut alias = ns
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if let Some(first) = alias.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+    format!("{}Procedures", alias)
+}
+
+fn write_type_imports(
+    file: &mut Vec<u8>,
+    names: BTreeSet<&str>,
+    from: &str,
+) -> Result<(), ExportError> {
+    if !names.is_empty() {
+        writeln!(
+            file,
+            "\nimport type {{ {} }} from \"{}\";",
+            names.into_iter().collect::<Vec<_>>().join(", "),
+            from
+        )?;
+    }
+    Ok(())
+}
+
+fn collect_references<'a>(dt: &DataType, type_map: &'a TypeMap, names: &mut BTreeSet<&'a str>) {
+    match dt {
+        DataType::Nullable(inner) => collect_references(inner, type_map, names),
+        DataType::List(list) => collect_references(list.ty(), type_map, names),
+        DataType::Map(map) => {
+            collect_references(map.key_ty(), type_map, names);
+            collect_references(map.value_ty(), type_map, names);
+        }
+        DataType::Struct(s) => collect_field_references(s.fields(), type_map, names),
+        DataType::Enum(e) => {
+            for (_, variant) in e.variants() {
+                collect_field_references(variant.fields(), type_map, names);
+            }
+        }
+        DataType::Tuple(tuple) => {
+            for dt in tuple.elements() {
+                collect_references(dt, type_map, names);
+            }
+        }
+        DataType::Reference(reference) => {
+            if let Some(dt) = type_map.get(reference.sid()) {
+                names.insert(dt.name());
+            }
+            for (_, dt) in reference.generics() {
+                collect_references(dt, type_map, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn collect_field_references<'a>(
+    fields: &Fields,
+    type_map: &'a TypeMap,
+    names: &mut BTreeSet<&'a str>,
+) {
+    match fields {
+        Fields::Unit => {}
+        Fields::Unnamed(fields) => {
+            for dt in fields.fields().iter().filter_map(|field| field.ty()) {
+                collect_references(dt, type_map, names);
+            }
+        }
+        Fields::Named(fields) => {
+            for dt in fields.fields().iter().filter_map(|(_, field)| field.ty()) {
+                collect_references(dt, type_map, names);
+            }
+        }
+    }
+}
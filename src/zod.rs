@@ -0,0 +1,340 @@
+use serde_json::Value;
+use specta::{
+    datatype::{EnumRepr, Fields, LiteralType, PrimitiveType},
+    DataType, TypeMap,
+};
+use specta_typescript::BigIntExportBehavior;
+
+use crate::{internal::ProcedureKind, json_schema::is_unit, Router};
+
+impl<TCtx, TMeta> Router<TCtx, TMeta>
+where
+    TCtx: Send + 'static,
+{
+    /// generate a Zod schema for every named type and a `procedureSchemas` object holding the input, result and error schemas of each procedure.
+    /// This is appended to the TypeScript bindings when [Config::export_zod_schemas](crate::Config::export_zod_schemas) is set.
+    pub(crate) fn zod_ts(&self) -> String {
+        let mut out = String::new();
+        // This must match how these integers are sent, not only how they are exported in the TypeScript bindings.
+        // Unless they are converted to strings they are sent as JSON numbers so `z.bigint()` would reject every value.
+        let bigint = match self.config.ts_bigint {
+            BigIntExportBehavior::String => "z.string()",
+            BigIntExportBehavior::Number => "z.number()",
+            _ => "z.coerce.bigint()",
+        };
+
+        for (_sid, dt) in self.typ_store.iter() {
+            let generics = match &dt.inner {
+                DataType::Struct(s) => s.generics().len(),
+                DataType::Enum(e) => e.generics().len(),
+                _ => 0,
+            };
+            // Generic parameters are validated as `z.any()` so the type arguments are left open.
+            let ty = match generics {
+                0 => dt.name().to_string(),
+                len => format!("{}<{}>", dt.name(), vec!["any"; len].join(", ")),
+            };
+
+            out.push_str(&format!(
+                "\nexport const {}Schema: z.ZodType<{}> = {};\n",
+                dt.name(),
+                ty,
+                schema(&dt.inner, &self.typ_store, bigint)
+            ));
+        }
+
+        let mut kinds = [
+            ("queries", Vec::new()),
+            ("mutations", Vec::new()),
+            ("subscriptions", Vec::new()),
+        ];
+        for procedure in self.procedures() {
+            let procedures = match procedure.kind() {
+                ProcedureKind::Query => &mut kinds[0].1,
+                ProcedureKind::Mutation => &mut kinds[1].1,
+                ProcedureKind::Subscription => &mut kinds[2].1,
+            };
+
+            let error = match is_unit(procedure.error()) {
+                // Procedures returning `rspc::Error` don't have a typed error.
+                true => String::new(),
+                false => format!(
+                    ", error: {}",
+                    schema(procedure.error(), &self.typ_store, bigint)
+                ),
+            };
+            procedures.push(format!(
+                "\n        {}: {{ input: {}, result: {}{} }}",
+                quote(procedure.key()),
+                schema(procedure.input(), &self.typ_store, bigint),
+                schema(procedure.result(), &self.typ_store, bigint),
+                error
+            ));
+        }
+
+        out.push_str("\nexport const procedureSchemas = {");
+        for (kind, procedures) in kinds {
+            out.push_str(&format!(
+                "\n    {}: {{{}\n    }},",
+                kind,
+                procedures.join(",")
+            ));
+        }
+        out.push_str("\n};\n");
+
+        out
+    }
+}
+
+fn quote(s: &str) -> String {
+    Value::from(s).to_string()
+}
+
+fn union(mut variants: Vec<String>) -> String {
+    match variants.len() {
+        0 => "z.never()".into(),
+        1 => variants.remove(0),
+        _ => format!("z.union([{}])", variants.join(", ")),
+    }
+}
+
+fn schema(dt: &DataType, type_map: &TypeMap, bigint: &str) -> String {
+    match dt {
+        DataType::Primitive(ty) => match ty {
+            PrimitiveType::i8 | PrimitiveType::i16 | PrimitiveType::i32 => {
+                "z.number().int()".into()
+            }
+            PrimitiveType::u8 | PrimitiveType::u16 | PrimitiveType::u32 => {
+                "z.number().int().nonnegative()".into()
+            }
+            PrimitiveType::i64
+            | PrimitiveType::i128
+            | PrimitiveType::isize
+            | PrimitiveType::u64
+            | PrimitiveType::u128
+            | PrimitiveType::usize => bigint.into(),
+            PrimitiveType::bool => "z.boolean()".into(),
+            PrimitiveType::char | PrimitiveType::String => "z.string()".into(),
+            _ => "z.number()".into(),
+        },
+        DataType::Literal(literal) => match literal {
+            LiteralType::bool(v) => format!("z.literal({})", v),
+            LiteralType::char(v) => format!("z.literal({})", quote(&v.to_string())),
+            LiteralType::String(v) => format!("z.literal({})", quote(v)),
+            LiteralType::None => "z.null()".into(),
+            // TODO: Numeric literals
+            _ => "z.any()".into(),
+        },
+        DataType::List(list) => {
+            let item = schema(list.ty(), type_map, bigint);
+            match list.length() {
+                Some(length) => format!("z.tuple([{}])", vec![item; length].join(", ")),
+                None => format!("z.array({})", item),
+            }
+        }
+        DataType::Map(map) => format!(
+            "z.record(z.string(), {})",
+            schema(map.value_ty(), type_map, bigint)
+        ),
+        DataType::Nullable(inner) => format!("{}.nullable()", schema(inner, type_map, bigint)),
+        DataType::Struct(s) => fields_schema(s.fields(), type_map, bigint),
+        DataType::Enum(e) => union(
+            e.variants()
+                .iter()
+                .filter(|(_, variant)| !variant.skip())
+                .map(|(name, variant)| {
+                    let fields = variant.fields();
+                    match e.repr() {
+                        EnumRepr::Untagged => fields_schema(fields, type_map, bigint),
+                        EnumRepr::External => match fields {
+                            Fields::Unit => format!("z.literal({})", quote(name)),
+                            _ => format!(
+                                "z.object({{ {}: {} }})",
+                                quote(name),
+                                fields_schema(fields, type_map, bigint)
+                            ),
+                        },
+                        EnumRepr::Internal { tag } => {
+                            let tag = format!(
+                                "z.object({{ {}: z.literal({}) }})",
+                                quote(tag),
+                                quote(name)
+                            );
+                            match fields {
+                                Fields::Unit => tag,
+                                _ => format!(
+                                    "{}.and({})",
+                                    tag,
+                                    fields_schema(fields, type_map, bigint)
+                                ),
+                            }
+                        }
+                        EnumRepr::Adjacent { tag, content } => match fields {
+                            Fields::Unit => format!(
+                                "z.object({{ {}: z.literal({}) }})",
+                                quote(tag),
+                                quote(name)
+                            ),
+                            _ => format!(
+                                "z.object({{ {}: z.literal({}), {}: {} }})",
+                                quote(tag),
+                                quote(name),
+                                quote(content),
+                                fields_schema(fields, type_map, bigint)
+                            ),
+                        },
+                    }
+                })
+                .collect(),
+        ),
+        DataType::Tuple(tuple) => match tuple.elements().len() {
+            0 => "z.null()".into(),
+            _ => format!(
+                "z.tuple([{}])",
+                tuple
+                    .elements()
+                    .iter()
+                    .map(|dt| schema(dt, type_map, bigint))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        },
+        // `z.lazy` allows the named schemas to be declared in any order and to be recursive.
+        DataType::Reference(reference) => match type_map.get(reference.sid()) {
+            Some(dt) => format!("z.lazy(() => {}Schema)", dt.name()),
+            None => "z.any()".into(),
+        },
+        // `Any`, `Unknown` and generic parameters can be anything.
+        _ => "z.any()".into(),
+    }
+}
+
+fn fields_schema(fields: &Fields, type_map: &TypeMap, bigint: &str) -> String {
+    match fields {
+        Fields::Unit => "z.null()".into(),
+        Fields::Unnamed(fields) => {
+            let mut items = fields
+                .fields()
+                .iter()
+                .filter_map(|field| field.ty())
+                .map(|dt| schema(dt, type_map, bigint))
+                .collect::<Vec<_>>();
+
+            // A newtype is serialized as it's inner value.
+            match items.len() {
+                1 => items.remove(0),
+                _ => format!("z.tuple([{}])", items.join(", ")),
+            }
+        }
+        Fields::Named(fields) => {
+            let mut properties = Vec::new();
+            let mut flattened = Vec::new();
+
+            if let Some(tag) = fields.tag() {
+                properties.push(format!("{}: z.string()", quote(tag)));
+            }
+
+            for (name, field) in fields.fields() {
+                // Fields which are `#[serde(skip)]` don't have a type.
+                let Some(dt) = field.ty() else {
+                    continue;
+                };
+
+                if field.flatten() {
+                    flattened.push(schema(dt, type_map, bigint));
+                    continue;
+                }
+
+                let schema = schema(dt, type_map, bigint);
+                properties.push(match field.optional() {
+                    true => format!("{}: {}.optional()", quote(name), schema),
+                    false => format!("{}: {}", quote(name), schema),
+                });
+            }
+
+            flattened.into_iter().fold(
+                format!("z.object({{ {} }})", properties.join(", ")),
+                |schema, flattened| format!("{}.and({})", schema, flattened),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, sync::Arc};
+
+    use serde_json::Value;
+    use specta_typescript::BigIntExportBehavior;
+
+    use crate::{
+        internal::jsonrpc::{self, handle_json_rpc, RequestId, RequestInner, ResponseInner},
+        Config, Router,
+    };
+
+    /// Whether the value passes the Zod schema of a 64 bit integer.
+    fn zod_accepts(schema: &str, value: &Value) -> bool {
+        match schema {
+            // `z.coerce.bigint()` passes the value to `BigInt()` which accepts integers and strings of integers.
+            "z.coerce.bigint()" => {
+                value.is_i64()
+                    || value.is_u64()
+                    || value.as_str().is_some_and(|s| s.parse::<i128>().is_ok())
+            }
+            "z.number()" => value.is_number(),
+            "z.string()" => value.is_string(),
+            _ => false,
+        }
+    }
+
+    #[tokio::test]
+    async fn bigint_schema_matches_response() {
+        for (behavior, schema) in [
+            (BigIntExportBehavior::BigInt, "z.coerce.bigint()"),
+            (BigIntExportBehavior::Number, "z.number()"),
+            (BigIntExportBehavior::String, "z.string()"),
+        ] {
+            let router = Arc::new(
+                Router::<()>::new()
+                    .config(Config::new().ts_bigint(behavior))
+                    .query("big", |t| t(|_, _: ()| Ok(u64::MAX)))
+                    .build(),
+            );
+
+            let zod = router.zod_ts();
+            assert!(
+                zod.contains(&format!(
+                    "\"big\": {{ input: z.null(), result: {} }}",
+                    schema
+                )),
+                "unexpected schema: {zod}"
+            );
+
+            let mut resp = None;
+            handle_json_rpc(
+                (),
+                jsonrpc::Request {
+                    jsonrpc: None,
+                    id: RequestId::Number(1),
+                    inner: RequestInner::Query {
+                        path: "big".into(),
+                        input: None,
+                    },
+                    last_event_id: None,
+                },
+                Cow::Borrowed(&router),
+                &mut resp,
+            )
+            .await;
+
+            let value = match resp.map(|resp| resp.result) {
+                Some(ResponseInner::Response(value)) => Some(value),
+                _ => None,
+            };
+            assert!(
+                matches!(&value, Some(value) if zod_accepts(schema, value)),
+                "`{schema}` rejects the response {value:?}"
+            );
+        }
+    }
+}